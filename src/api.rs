@@ -16,92 +16,801 @@
 
 // ----------------------------------------------------------------
 
-use dioxus::prelude::{ReadableOptionExt, Signal, Writable};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use dioxus::prelude::{Readable, Signal, Writable};
 // Define the Hackernews API
-use futures::future::join_all;
+use futures::future::{join_all, FutureExt, LocalBoxFuture};
+use futures::stream::{self, StreamExt};
+use once_cell::sync::Lazy;
+use reqwest::header::{CONTENT_TYPE, RETRY_AFTER, USER_AGENT};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use tokio::sync::Semaphore;
 
-use crate::api;
-use crate::types::{Comment, PreviewState, StoryItem, StoryPageData};
+use crate::error::ApiError;
+use crate::types::{
+    Comment, Feed, Item, ItemType, LinkPreview, PollOption, PreviewState, StoryCache, StoryItem,
+    StoryPageData, User,
+};
 
 // ----------------------------------------------------------------
 
 pub static BASE_API_URL: &str = "https://hacker-news.firebaseio.com/v0/";
 pub static ITEM_API: &str = "item/";
 pub static USER_API: &str = "user/";
-const COMMENT_DEPTH: i64 = 2;
+const COMMENT_DEPTH: usize = 2;
+pub const MAX_CONCURRENT_REQUESTS: usize = 8;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Requests per second all clients share unless [`ApiClient::with_rate_limit`]
+/// gives one its own budget.
+pub const DEFAULT_REQUESTS_PER_SECOND: f64 = 20.0;
+/// Identifies this app to the API unless [`ApiClient::with_user_agent`] says otherwise.
+pub const DEFAULT_USER_AGENT: &str = concat!("hackernews-dioxus/", env!("CARGO_PKG_VERSION"));
+/// How much of a linked page is downloaded and scanned for its preview; the
+/// tags live in `<head>`, and the rest of the body is never read.
+const LINK_PREVIEW_MAX_BYTES: usize = 512 * 1024;
+/// The longest a `Retry-After` header can make a retry wait; anything longer
+/// is better reported than sat through.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// One connection pool for every request, so concurrent comment fetches reuse
+/// connections instead of each opening their own.
+static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("failed to build the HTTP client")
+});
+
+/// One budget for every client, so opening more of them doesn't multiply the load on the API.
+static SHARED_RATE_LIMITER: Lazy<Arc<RateLimiter>> =
+    Lazy::new(|| Arc::new(RateLimiter::new(DEFAULT_REQUESTS_PER_SECOND)));
+
+static DEFAULT_CLIENT: Lazy<ApiClient> = Lazy::new(ApiClient::default);
 
 // ----------------------------------------------------------------
 
-pub async fn get_story_preview(id: i64) -> Result<StoryItem, reqwest::Error> {
-    let url = format!("{}{}{}.json", BASE_API_URL, ITEM_API, id);
-    reqwest::get(&url).await?.json().await
+/// A page of stories, alongside the errors of the ids that didn't resolve.
+#[derive(Debug, Default)]
+pub struct StoryBatch {
+    pub stories: Vec<StoryItem>,
+    pub failures: Vec<ApiError>,
+    /// Where the next page starts in the id list; past `offset + count` when
+    /// dead or deleted stories had to be made up for.
+    pub next_offset: usize,
+}
+
+/// How failed HTTP requests are retried: up to `max_retries` extra attempts,
+/// waiting `base_delay`, then twice that, and so on between them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before retry number `attempt`, counting from 0.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay.saturating_mul(2u32.saturating_pow(attempt))
+    }
+
+    /// Runs `operation` until it succeeds, fails with a non-transient error,
+    /// or runs out of retries; the last error is returned. A server asking for
+    /// a longer wait with `Retry-After` gets it, up to [`MAX_RETRY_AFTER`].
+    pub async fn run<T, F, Fut>(&self, mut operation: F) -> Result<T, ApiError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match operation().await {
+                Err(err) if err.is_transient() && attempt < self.max_retries => {
+                    tracing::debug!(attempt, error = %err, "retrying");
+                    let delay = self
+                        .delay(attempt)
+                        .max(err.retry_after().unwrap_or_default());
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// A token bucket holding up to one second's worth of requests: bursts up to
+/// that size go out at once, after which requests are spaced out evenly.
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests_per_second: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: DateTime<Utc>,
 }
 
-pub async fn get_stories(count: usize) -> Result<Vec<StoryItem>, reqwest::Error> {
-    let url = format!("{}topstories.json", BASE_API_URL);
-    let stories_ids = &reqwest::get(&url).await?.json::<Vec<i64>>().await?[..count];
+impl RateLimiter {
+    /// Allows `requests_per_second` on average; values below 1 are treated as 1.
+    pub fn new(requests_per_second: f64) -> Self {
+        let requests_per_second = requests_per_second.max(1.0);
+        Self {
+            requests_per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: requests_per_second,
+                refilled_at: Utc::now(),
+            }),
+        }
+    }
+
+    pub fn requests_per_second(&self) -> f64 {
+        self.requests_per_second
+    }
 
-    let story_futures = stories_ids[..usize::min(stories_ids.len(), count)]
-        .iter()
-        .map(|&story_id| get_story_preview(story_id));
-    let stories = join_all(story_futures)
-        .await
-        .into_iter()
-        .filter_map(|story| story.ok())
-        .collect();
-    Ok(stories)
+    /// Waits until a request may be sent, then takes its token.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap_or_else(|err| err.into_inner());
+                // `Instant` isn't available on wasm, so time is read through chrono.
+                let now = Utc::now();
+                let elapsed = (now - bucket.refilled_at).to_std().unwrap_or_default();
+                bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * self.requests_per_second)
+                    .min(self.requests_per_second);
+                bucket.refilled_at = now;
+
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / self.requests_per_second)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
 }
 
-pub async fn get_story(id: i64) -> Result<StoryPageData, reqwest::Error> {
-    let url = format!("{}{}{}.json", BASE_API_URL, ITEM_API, id);
-    let mut story = reqwest::get(&url).await?.json::<StoryPageData>().await?;
-    let comment_futures = story.item.kids.iter().map(|&id| get_comment(id));
-    let comments = join_all(comment_futures)
-        .await
-        .into_iter()
-        .filter_map(|c| c.ok())
-        .collect();
+// ----------------------------------------------------------------
+
+/// A Hackernews API client bound to one base URL, e.g. a mock server in tests.
+/// [`ApiClient::default`] talks to the real Firebase API. All clients share one
+/// connection pool unless [`ApiClient::with_client`] is used.
+#[derive(Clone, Debug)]
+pub struct ApiClient {
+    base_url: String,
+    client: reqwest::Client,
+    retry_policy: RetryPolicy,
+    /// Permits for requests in flight, shared by clones of this client.
+    limiter: Arc<Semaphore>,
+    max_concurrent_requests: usize,
+    user_agent: String,
+    rate_limiter: Arc<RateLimiter>,
+}
 
-    story.comments = comments;
-    Ok(story)
+impl Default for ApiClient {
+    fn default() -> Self {
+        Self::new(BASE_API_URL)
+    }
 }
 
-pub async fn resolve_story(
-    mut full_story: Signal<Option<StoryPageData>>,
+impl ApiClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+
+        Self {
+            base_url,
+            client: HTTP_CLIENT.clone(),
+            retry_policy: RetryPolicy::default(),
+            limiter: Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS)),
+            max_concurrent_requests: MAX_CONCURRENT_REQUESTS,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            rate_limiter: SHARED_RATE_LIMITER.clone(),
+        }
+    }
+
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Caps the requests this client has in flight at once, across every comment
+    /// level. More loads large threads faster; fewer is politer to the API and
+    /// less likely to be throttled. Values below 1 are treated as 1.
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        let max_concurrent_requests = max_concurrent_requests.max(1);
+        self.limiter = Arc::new(Semaphore::new(max_concurrent_requests));
+        self.max_concurrent_requests = max_concurrent_requests;
+        self
+    }
+
+    /// Sent as the `User-Agent` of every request, e.g. to name a self-hosted
+    /// deployment. Browsers may keep their own user agent regardless.
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Gives this client (and its clones) its own budget of requests per second
+    /// instead of the one shared by all clients.
+    pub fn with_rate_limit(mut self, requests_per_second: f64) -> Self {
+        self.rate_limiter = Arc::new(RateLimiter::new(requests_per_second));
+        self
+    }
+
+    pub fn max_concurrent_requests(&self) -> usize {
+        self.max_concurrent_requests
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    // ----------------------------------------------------------------
+
+    /// Sends a GET once the rate limit allows it and a request permit is free;
+    /// the permit is released when the response headers arrive, before the
+    /// body is read.
+    async fn send(&self, url: &str) -> Result<reqwest::Response, ApiError> {
+        self.rate_limiter.acquire().await;
+        // The semaphore is never closed, so acquiring can't fail.
+        let _permit = self.limiter.acquire().await.ok();
+        let request = self.client.get(url).header(USER_AGENT, &self.user_agent);
+        let response = request.send().await?;
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(ApiError::RateLimited(retry_after(&response)));
+        }
+        Ok(response)
+    }
+
+    async fn fetch_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, ApiError> {
+        let started = Utc::now();
+        let result = self
+            .retry_policy
+            .run(|| async {
+                let response = self.send(url).await?;
+                let body = response.error_for_status()?.bytes().await?;
+                Ok(serde_json::from_slice(&body)?)
+            })
+            .await;
+        log_outcome(url, started, &result);
+        result
+    }
+
+    async fn fetch_item<T: DeserializeOwned>(&self, id: i64) -> Result<T, ApiError> {
+        let url = format!("{}{}{}.json", self.base_url, ITEM_API, id);
+        self.fetch_optional(&url)
+            .await?
+            .ok_or(ApiError::NotFound(id))
+    }
+
+    /// Like `fetch_json`, but maps a 404 or a `null` body to `None`.
+    async fn fetch_optional<T: DeserializeOwned>(&self, url: &str) -> Result<Option<T>, ApiError> {
+        let started = Utc::now();
+        let result = self
+            .retry_policy
+            .run(|| async {
+                let response = self.send(url).await?;
+                if response.status() == StatusCode::NOT_FOUND {
+                    return Ok(None);
+                }
+
+                // HN answers `null` rather than 404 for ids that don't exist.
+                let body = response.error_for_status()?.bytes().await?;
+                Ok(serde_json::from_slice::<Option<T>>(&body)?)
+            })
+            .await;
+        log_outcome(url, started, &result);
+        result
+    }
+
+    // ----------------------------------------------------------------
+
+    /// Fetches any item, whatever its type.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_item(&self, id: i64) -> Result<Item, ApiError> {
+        self.fetch_item(id).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_story_preview(&self, id: i64) -> Result<StoryItem, ApiError> {
+        self.fetch_item(id).await
+    }
+
+    pub async fn get_stories(&self, count: usize) -> Result<Vec<StoryItem>, ApiError> {
+        self.get_feed(Feed::Top, count).await
+    }
+
+    pub async fn get_new_stories(&self, count: usize) -> Result<Vec<StoryItem>, ApiError> {
+        self.get_feed(Feed::New, count).await
+    }
+
+    /// Fetches the first `count` renderable stories of `feed`, reading further
+    /// down the id list to make up for dead or deleted ones.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_feed(&self, feed: Feed, count: usize) -> Result<Vec<StoryItem>, ApiError> {
+        let stories_ids = self.get_story_ids(feed).await?;
+        self.get_stories_paged(&stories_ids, 0, count).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_story_ids(&self, feed: Feed) -> Result<Vec<i64>, ApiError> {
+        let url = format!("{}{}.json", self.base_url, feed.endpoint());
+        self.fetch_json(&url).await
+    }
+
+    /// Fetches `count` stories of `ids` from `offset` on, clamped to the id list,
+    /// so callers can fetch the id list once and page through it. Dead and
+    /// deleted stories are left out, as are stories that failed to load.
+    pub async fn get_stories_paged(
+        &self,
+        ids: &[i64],
+        offset: usize,
+        count: usize,
+    ) -> Result<Vec<StoryItem>, ApiError> {
+        Ok(self.get_stories_batch(ids, offset, count).await.stories)
+    }
+
+    /// Like `get_stories_paged`, but keeps the errors of the stories that failed
+    /// to load instead of dropping them silently. Dead and deleted stories are
+    /// made up for by reading further down `ids`; failed ones are not, so they
+    /// can still be reported against the page.
+    #[tracing::instrument(level = "debug", skip(self, ids), fields(ids = ids.len()))]
+    pub async fn get_stories_batch(&self, ids: &[i64], offset: usize, count: usize) -> StoryBatch {
+        read_stories_batch(ids, offset, count, |id| self.get_story_preview(id)).await
+    }
+
+    pub async fn get_story(&self, id: i64) -> Result<StoryPageData, ApiError> {
+        self.get_story_with_depth(id, COMMENT_DEPTH).await
+    }
+
+    /// Fetches a story with `max_depth` levels of comments; the deepest fetched
+    /// comments keep their `kids` ids but have no `sub_comments`.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_story_with_depth(
+        &self,
+        id: i64,
+        max_depth: usize,
+    ) -> Result<StoryPageData, ApiError> {
+        let mut story = self.fetch_item::<StoryPageData>(id).await?;
+        if max_depth > 0 {
+            story.comments = self.get_comments(&story.item.kids, max_depth - 1).await;
+        }
+        if story.item.item_type() == ItemType::Poll {
+            story.poll_options = self.get_poll_options(&story.item.parts).await;
+        }
+        Ok(story)
+    }
+
+    /// Resolves the whole thread of `id` and returns it as pretty-printed JSON.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn export_story_json(&self, id: i64) -> Result<String, ApiError> {
+        // Unbounded: the recursion stops where a comment has no more kids.
+        let story = self.get_story_with_depth(id, usize::MAX).await?;
+        Ok(serde_json::to_string_pretty(&story)?)
+    }
+
+    /// Fetches a poll with its options, in `parts` order, but without comments.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_poll(&self, id: i64) -> Result<StoryPageData, ApiError> {
+        let mut poll = self.fetch_item::<StoryPageData>(id).await?;
+        poll.poll_options = self.get_poll_options(&poll.item.parts).await;
+        Ok(poll)
+    }
+
+    async fn get_poll_options(&self, ids: &[i64]) -> Vec<PollOption> {
+        let option_futures = ids.iter().map(|&id| self.fetch_item::<PollOption>(id));
+        join_all(option_futures)
+            .await
+            .into_iter()
+            .filter_map(|option| option.ok())
+            .collect()
+    }
+
+    #[async_recursion::async_recursion(? Send)]
+    pub async fn get_comment_with_depth(&self, id: i64, depth: usize) -> Result<Comment, ApiError> {
+        let mut comment = self.get_comment(id).await?;
+        if depth > 0 {
+            comment.sub_comments = self.get_comments(&comment.kids, depth - 1).await;
+        }
+        Ok(comment)
+    }
+
+    /// Fetches sibling comments concurrently, keeping them in `ids` order and
+    /// skipping failures. Requests across all levels share the client's
+    /// [`ApiClient::with_max_concurrent_requests`] limit.
+    #[tracing::instrument(level = "debug", skip(self, ids), fields(ids = ids.len()))]
+    pub async fn get_comments(&self, ids: &[i64], depth: usize) -> Vec<Comment> {
+        stream::iter(ids.iter().map(|&id| self.get_comment_with_depth(id, depth)))
+            .buffered(self.max_concurrent_requests)
+            .filter_map(|comment| async move { comment.ok() })
+            .collect()
+            .await
+    }
+
+    /// Fetches a single comment; its replies are left as `kids` ids, with no
+    /// `sub_comments`, so the caller decides whether to recurse.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_comment(&self, comment_id: i64) -> Result<Comment, ApiError> {
+        self.fetch_item(comment_id).await
+    }
+
+    // ----------------------------------------------------------------
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_user(&self, username: &str) -> Result<User, ApiError> {
+        let url = format!("{}{}{}.json", self.base_url, USER_API, username);
+        self.fetch_optional(&url)
+            .await?
+            .ok_or_else(|| ApiError::UserNotFound(username.to_string()))
+    }
+
+    // ----------------------------------------------------------------
+
+    /// Fetches the page a story links to and reads its OpenGraph preview.
+    /// Anything that isn't HTML (PDFs, images, ...) gets an empty preview.
+    ///
+    /// Not retried: the page isn't ours, and a preview is only nice to have.
+    /// In the browser most sites refuse the cross-origin request, which
+    /// surfaces as a network error.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn fetch_link_preview(&self, url: &str) -> Result<LinkPreview, ApiError> {
+        let started = Utc::now();
+        let result = async {
+            let response = self.send(url).await?.error_for_status()?;
+            let is_html = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|content_type| content_type.to_str().ok())
+                .and_then(|content_type| content_type.split(';').next())
+                .is_some_and(|mime| {
+                    let mime = mime.trim();
+                    mime.eq_ignore_ascii_case("text/html")
+                        || mime.eq_ignore_ascii_case("application/xhtml+xml")
+                });
+            if !is_html {
+                return Ok(LinkPreview::default());
+            }
+
+            // Redirects may have moved the page, and relative images with it.
+            let page_url = response.url().to_string();
+            // Read in chunks, so a huge page never gets downloaded past the limit.
+            let mut chunks = std::pin::pin!(response.bytes_stream());
+            let mut body = Vec::new();
+            while let Some(chunk) = chunks.next().await {
+                body.extend_from_slice(&chunk?);
+                if body.len() >= LINK_PREVIEW_MAX_BYTES {
+                    body.truncate(LINK_PREVIEW_MAX_BYTES);
+                    break;
+                }
+            }
+            Ok(LinkPreview::from_html(
+                &String::from_utf8_lossy(&body),
+                &page_url,
+            ))
+        }
+        .await;
+        log_outcome(url, started, &result);
+        result
+    }
+}
+
+/// The wait a `Retry-After: <seconds>` header asks for, capped at
+/// [`MAX_RETRY_AFTER`]; HTTP dates aren't understood and count as unset.
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let seconds = response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_RETRY_AFTER))
+}
+
+/// The paging behind [`ApiClient::get_stories_batch`], over whatever `fetch`es
+/// a single story, so providers other than the client share it.
+async fn read_stories_batch<F, Fut>(
+    ids: &[i64],
+    offset: usize,
+    count: usize,
+    fetch: F,
+) -> StoryBatch
+where
+    F: Fn(i64) -> Fut,
+    Fut: Future<Output = Result<StoryItem, ApiError>>,
+{
+    let mut batch = StoryBatch {
+        next_offset: usize::min(offset, ids.len()),
+        ..StoryBatch::default()
+    };
+
+    while batch.stories.len() + batch.failures.len() < count && batch.next_offset < ids.len() {
+        let start = batch.next_offset;
+        let missing = count - batch.stories.len() - batch.failures.len();
+        let end = usize::min(start.saturating_add(missing), ids.len());
+
+        let story_futures = ids[start..end].iter().map(|&story_id| fetch(story_id));
+        for story in join_all(story_futures).await {
+            match story {
+                Ok(story) if story.is_renderable() => batch.stories.push(story),
+                Ok(_) => {}
+                Err(err) => batch.failures.push(err),
+            }
+        }
+        batch.next_offset = end;
+    }
+    batch
+}
+
+/// Logs how a request to `url` went: at debug level, or warn when it failed.
+fn log_outcome<T>(url: &str, started: DateTime<Utc>, result: &Result<T, ApiError>) {
+    let elapsed_ms = (Utc::now() - started).num_milliseconds();
+    match result {
+        Ok(_) => tracing::debug!(url, elapsed_ms, "fetched"),
+        Err(err) => tracing::warn!(url, elapsed_ms, error = %err, "fetch failed"),
+    }
+}
+
+// ----------------------------------------------------------------
+
+// Shortcuts against the real Firebase API, as used by the UI.
+
+pub async fn get_item(id: i64) -> Result<Item, ApiError> {
+    DEFAULT_CLIENT.get_item(id).await
+}
+
+pub async fn get_story_preview(id: i64) -> Result<StoryItem, ApiError> {
+    DEFAULT_CLIENT.get_story_preview(id).await
+}
+
+pub async fn get_stories(count: usize) -> Result<Vec<StoryItem>, ApiError> {
+    DEFAULT_CLIENT.get_stories(count).await
+}
+
+pub async fn get_new_stories(count: usize) -> Result<Vec<StoryItem>, ApiError> {
+    DEFAULT_CLIENT.get_new_stories(count).await
+}
+
+pub async fn get_feed(feed: Feed, count: usize) -> Result<Vec<StoryItem>, ApiError> {
+    DEFAULT_CLIENT.get_feed(feed, count).await
+}
+
+pub async fn get_story_ids(feed: Feed) -> Result<Vec<i64>, ApiError> {
+    DEFAULT_CLIENT.get_story_ids(feed).await
+}
+
+pub async fn get_stories_paged(
+    ids: &[i64],
+    offset: usize,
+    count: usize,
+) -> Result<Vec<StoryItem>, ApiError> {
+    DEFAULT_CLIENT.get_stories_paged(ids, offset, count).await
+}
+
+pub async fn get_stories_batch(ids: &[i64], offset: usize, count: usize) -> StoryBatch {
+    DEFAULT_CLIENT.get_stories_batch(ids, offset, count).await
+}
+
+pub async fn get_story(id: i64) -> Result<StoryPageData, ApiError> {
+    DEFAULT_CLIENT.get_story(id).await
+}
+
+pub async fn get_story_with_depth(id: i64, max_depth: usize) -> Result<StoryPageData, ApiError> {
+    DEFAULT_CLIENT.get_story_with_depth(id, max_depth).await
+}
+
+pub async fn export_story_json(id: i64) -> Result<String, ApiError> {
+    DEFAULT_CLIENT.export_story_json(id).await
+}
+
+pub async fn get_poll(id: i64) -> Result<StoryPageData, ApiError> {
+    DEFAULT_CLIENT.get_poll(id).await
+}
+
+pub async fn fetch_link_preview(url: &str) -> Result<LinkPreview, ApiError> {
+    DEFAULT_CLIENT.fetch_link_preview(url).await
+}
+
+pub async fn get_comment_with_depth(id: i64, depth: usize) -> Result<Comment, ApiError> {
+    DEFAULT_CLIENT.get_comment_with_depth(id, depth).await
+}
+
+pub async fn get_comments(ids: &[i64], depth: usize) -> Vec<Comment> {
+    DEFAULT_CLIENT.get_comments(ids, depth).await
+}
+
+pub async fn get_comment(comment_id: i64) -> Result<Comment, ApiError> {
+    DEFAULT_CLIENT.get_comment(comment_id).await
+}
+
+pub async fn get_user(username: &str) -> Result<User, ApiError> {
+    DEFAULT_CLIENT.get_user(username).await
+}
+
+// ----------------------------------------------------------------
+
+/// What the UI needs from a source of stories, so components can be run
+/// against a stub instead of the network. Futures are boxed to keep the trait
+/// usable as `Rc<dyn StoryProvider>` in a context.
+pub trait StoryProvider {
+    fn get_stories(&self, count: usize) -> LocalBoxFuture<'_, Result<Vec<StoryItem>, ApiError>>;
+
+    fn get_story_ids(&self, feed: Feed) -> LocalBoxFuture<'_, Result<Vec<i64>, ApiError>>;
+
+    /// Fetches `count` stories of `ids` from `offset` on through
+    /// [`Self::get_story_preview`], like [`ApiClient::get_stories_batch`].
+    fn get_stories_batch<'a>(
+        &'a self,
+        ids: &'a [i64],
+        offset: usize,
+        count: usize,
+    ) -> LocalBoxFuture<'a, StoryBatch> {
+        read_stories_batch(ids, offset, count, move |id| self.get_story_preview(id)).boxed_local()
+    }
+
+    /// Fetches a story with `max_depth` levels of comments.
+    fn resolve(
+        &self,
+        id: i64,
+        max_depth: usize,
+    ) -> LocalBoxFuture<'_, Result<StoryPageData, ApiError>>;
+
+    /// Fetches the comments `ids` with `depth` levels of their replies.
+    fn get_comments<'a>(&'a self, ids: &'a [i64], depth: usize)
+        -> LocalBoxFuture<'a, Vec<Comment>>;
+
+    /// Resolves the whole thread of `id` as pretty-printed JSON.
+    fn export_story_json(&self, id: i64) -> LocalBoxFuture<'_, Result<String, ApiError>>;
+
+    fn get_item(&self, id: i64) -> LocalBoxFuture<'_, Result<Item, ApiError>>;
+
+    /// Fetches a story without its comments.
+    fn get_story_preview(&self, id: i64) -> LocalBoxFuture<'_, Result<StoryItem, ApiError>>;
+
+    fn get_user<'a>(&'a self, username: &'a str) -> LocalBoxFuture<'a, Result<User, ApiError>>;
+
+    /// Fetches the OpenGraph preview of the page a story links to.
+    fn fetch_link_preview<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> LocalBoxFuture<'a, Result<LinkPreview, ApiError>>;
+}
+
+/// The [`StoryProvider`] backed by the HN Firebase API.
+#[derive(Clone, Debug, Default)]
+pub struct FirebaseProvider {
+    client: ApiClient,
+}
+
+impl FirebaseProvider {
+    pub fn new(client: ApiClient) -> Self {
+        Self { client }
+    }
+}
+
+impl StoryProvider for FirebaseProvider {
+    fn get_stories(&self, count: usize) -> LocalBoxFuture<'_, Result<Vec<StoryItem>, ApiError>> {
+        self.client.get_stories(count).boxed_local()
+    }
+
+    fn get_story_ids(&self, feed: Feed) -> LocalBoxFuture<'_, Result<Vec<i64>, ApiError>> {
+        self.client.get_story_ids(feed).boxed_local()
+    }
+
+    fn get_stories_batch<'a>(
+        &'a self,
+        ids: &'a [i64],
+        offset: usize,
+        count: usize,
+    ) -> LocalBoxFuture<'a, StoryBatch> {
+        self.client
+            .get_stories_batch(ids, offset, count)
+            .boxed_local()
+    }
+
+    fn resolve(
+        &self,
+        id: i64,
+        max_depth: usize,
+    ) -> LocalBoxFuture<'_, Result<StoryPageData, ApiError>> {
+        self.client
+            .get_story_with_depth(id, max_depth)
+            .boxed_local()
+    }
+
+    fn get_comments<'a>(
+        &'a self,
+        ids: &'a [i64],
+        depth: usize,
+    ) -> LocalBoxFuture<'a, Vec<Comment>> {
+        self.client.get_comments(ids, depth).boxed_local()
+    }
+
+    fn export_story_json(&self, id: i64) -> LocalBoxFuture<'_, Result<String, ApiError>> {
+        self.client.export_story_json(id).boxed_local()
+    }
+
+    fn get_item(&self, id: i64) -> LocalBoxFuture<'_, Result<Item, ApiError>> {
+        self.client.get_item(id).boxed_local()
+    }
+
+    fn get_story_preview(&self, id: i64) -> LocalBoxFuture<'_, Result<StoryItem, ApiError>> {
+        self.client.get_story_preview(id).boxed_local()
+    }
+
+    fn get_user<'a>(&'a self, username: &'a str) -> LocalBoxFuture<'a, Result<User, ApiError>> {
+        self.client.get_user(username).boxed_local()
+    }
+
+    fn fetch_link_preview<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> LocalBoxFuture<'a, Result<LinkPreview, ApiError>> {
+        self.client.fetch_link_preview(url).boxed_local()
+    }
+}
+
+/// Shows `story_id` in the preview: straight from `cache` when it is fresh,
+/// otherwise fetched through `provider` (falling back to a stale copy on failure).
+pub async fn resolve_story_with(
+    provider: &dyn StoryProvider,
+    mut cache: Signal<StoryCache>,
     mut preview_state: Signal<PreviewState>,
     story_id: i64,
+    max_depth: usize,
 ) {
-    if let Some(cached) = full_story.as_ref() {
-        *preview_state.write() = PreviewState::Loaded(cached.clone());
+    let cached = cache.read().get(story_id).cloned();
+    if let Some(cached) = cached {
+        *preview_state.write() = PreviewState::Loaded(cached);
         return;
     }
 
     *preview_state.write() = PreviewState::Loading;
-    if let Ok(story) = api::get_story(story_id).await {
-        *preview_state.write() = PreviewState::Loaded(story.clone());
-        *full_story.write() = Some(story);
-    }
-}
-
-#[async_recursion::async_recursion(? Send)]
-pub async fn get_comment_with_depth(id: i64, depth: i64) -> Result<Comment, reqwest::Error> {
-    let url = format!("{}{}{}.json", BASE_API_URL, ITEM_API, id);
-    let mut comment = reqwest::get(&url).await?.json::<Comment>().await?;
-    if depth > 0 {
-        let sub_comments_futures = comment
-            .kids
-            .iter()
-            .map(|story_id| get_comment_with_depth(*story_id, depth - 1));
-        comment.sub_comments = join_all(sub_comments_futures)
-            .await
-            .into_iter()
-            .filter_map(|c| c.ok())
-            .collect();
+    match provider.resolve(story_id, max_depth).await {
+        Ok(story) => {
+            let hidden = story.hidden_comment_count();
+            if hidden > 0 {
+                tracing::debug!(story_id, hidden, "comments missing from the fetched thread");
+            }
+            *preview_state.write() = PreviewState::Loaded(story.clone());
+            let mut cache = cache.write();
+            cache.invalidate_expired();
+            cache.insert(story_id, story);
+        }
+        Err(err) => {
+            let stale = cache.read().get_stale(story_id).cloned();
+            *preview_state.write() = match stale {
+                Some(stale) => PreviewState::Loaded(stale),
+                None => PreviewState::Failed {
+                    story_id,
+                    timed_out: matches!(err, ApiError::Timeout),
+                },
+            };
+        }
     }
-    Ok(comment)
-}
-
-pub async fn get_comment(comment_id: i64) -> Result<Comment, reqwest::Error> {
-    let comment = get_comment_with_depth(comment_id, COMMENT_DEPTH).await?;
-    Ok(comment)
 }
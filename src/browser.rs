@@ -0,0 +1,165 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+use dioxus::prelude::{eval, UseEval};
+
+// ----------------------------------------------------------------
+
+// Small helpers around webview APIs, run through `eval` so they work on
+// desktop and web alike.
+
+/// Saves `contents` as a file named `filename` through a temporary blob URL.
+pub fn download(filename: &str, mime: &str, contents: &str) {
+    let script = format!(
+        r#"
+        const blob = new Blob([{contents}], {{ type: {mime} }});
+        const url = URL.createObjectURL(blob);
+        const link = document.createElement("a");
+        link.href = url;
+        link.download = {filename};
+        link.click();
+        URL.revokeObjectURL(url);
+        "#,
+        contents = quote(contents),
+        mime = quote(mime),
+        filename = quote(filename),
+    );
+    let _ = eval(&script);
+}
+
+/// Opens `url` in a new browser window.
+pub fn open(url: &str) {
+    let _ = eval(&format!(
+        "window.open({}, \"_blank\", \"noopener\");",
+        quote(url)
+    ));
+}
+
+/// Scrolls the element with the given id into view, if it isn't already.
+pub fn scroll_into_view(element_id: &str) {
+    let script = format!(
+        "document.getElementById({})?.scrollIntoView({{ block: \"nearest\" }});",
+        quote(element_id)
+    );
+    let _ = eval(&script);
+}
+
+/// Writes `text` to the clipboard, returning whether the browser allowed it.
+pub async fn copy_to_clipboard(text: &str) -> bool {
+    let script = format!(
+        r#"
+        Promise.resolve()
+            .then(() => navigator.clipboard.writeText({}))
+            .then(() => dioxus.send(true), () => dioxus.send(false));
+        "#,
+        quote(text)
+    );
+    let mut copy = eval(&script);
+    matches!(copy.recv().await, Ok(serde_json::Value::Bool(true)))
+}
+
+/// Opens the system share sheet with `text`, returning whether there was one;
+/// a reader dismissing the sheet still counts as handled.
+pub async fn share(text: &str) -> bool {
+    let script = format!(
+        r#"
+        if (navigator.share) {{
+            navigator.share({{ text: {} }})
+                .then(() => dioxus.send(true), (error) => dioxus.send(error?.name === "AbortError"));
+        }} else {{
+            dioxus.send(false);
+        }}
+        "#,
+        quote(text)
+    );
+    let mut share = eval(&script);
+    matches!(share.recv().await, Ok(serde_json::Value::Bool(true)))
+}
+
+/// Selects the contents of the input with the given id, ready for a manual copy.
+pub fn select_text(element_id: &str) {
+    let script = format!("document.getElementById({})?.select();", quote(element_id));
+    let _ = eval(&script);
+}
+
+/// Like [`scroll_into_view`], but returns whether the element was there.
+pub async fn reveal(element_id: &str) -> bool {
+    let script = format!(
+        r#"
+        const element = document.getElementById({});
+        element?.scrollIntoView({{ behavior: "smooth", block: "start" }});
+        return element != null;
+        "#,
+        quote(element_id)
+    );
+    matches!(
+        eval(&script).join().await,
+        Ok(serde_json::Value::Bool(true))
+    )
+}
+
+/// How far the element with the given id is scrolled, or `None` if it is
+/// missing or its content fits without scrolling.
+pub async fn scroll_top(element_id: &str) -> Option<f64> {
+    let script = format!(
+        r#"
+        const element = document.getElementById({});
+        return element && element.scrollHeight > element.clientHeight ? element.scrollTop : null;
+        "#,
+        quote(element_id)
+    );
+    eval(&script).join().await.ok()?.as_f64()
+}
+
+/// Smoothly scrolls the element with the given id back to its top.
+pub fn scroll_to_top(element_id: &str) {
+    let script = format!(
+        "document.getElementById({})?.scrollTo({{ top: 0, behavior: \"smooth\" }});",
+        quote(element_id)
+    );
+    let _ = eval(&script);
+}
+
+/// A random number in `[0, 1)` from `Math.random`, or `None` if the webview
+/// didn't answer.
+pub async fn random() -> Option<f64> {
+    eval("return Math.random();").join().await.ok()?.as_f64()
+}
+
+/// Listens for `key` (e.g. `"Escape"`) on the whole document, whatever has
+/// focus; each press arrives as a message on the returned eval. A later call
+/// for the same key replaces the earlier listener.
+pub fn key_presses(key: &str) -> UseEval {
+    let script = format!(
+        r#"
+        const name = "hackernews-keydown-" + {key};
+        if (window[name]) document.removeEventListener("keydown", window[name]);
+        window[name] = (event) => {{
+            if (event.key === {key}) dioxus.send(true);
+        }};
+        document.addEventListener("keydown", window[name]);
+        "#,
+        key = quote(key)
+    );
+    eval(&script)
+}
+
+/// Quotes `value` as a JavaScript string literal.
+pub(crate) fn quote(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
@@ -0,0 +1,109 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+use std::fmt;
+use std::time::Duration;
+
+// ----------------------------------------------------------------
+
+// Define the Hackernews API errors
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request could not be sent or the server answered with an error status.
+    Network(reqwest::Error),
+    /// The response body was not the JSON shape we expected.
+    Deserialize(serde_json::Error),
+    /// The item does not exist (HN answers `null` for unknown ids).
+    NotFound(i64),
+    /// The user does not exist.
+    UserNotFound(String),
+    /// The server took longer than the client's request timeout to answer.
+    Timeout,
+    /// The server answered 429 Too Many Requests, with how long its
+    /// `Retry-After` header asked us to wait, if it said.
+    RateLimited(Option<Duration>),
+}
+
+impl ApiError {
+    /// Whether retrying the same request might succeed, i.e. a network failure,
+    /// a server-side error status or rate limiting rather than another client
+    /// error or a bad payload.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ApiError::Network(err) => !err.status().is_some_and(|status| {
+                status.is_client_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS
+            }),
+            ApiError::RateLimited(_) => true,
+            // Retrying a slow endpoint would multiply the time the UI waits.
+            ApiError::Deserialize(_)
+            | ApiError::NotFound(_)
+            | ApiError::UserNotFound(_)
+            | ApiError::Timeout => false,
+        }
+    }
+
+    /// How long the server asked us to wait before trying again.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ApiError::RateLimited(retry_after) => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Network(err) => write!(f, "network error: {err}"),
+            ApiError::Deserialize(err) => write!(f, "unexpected response: {err}"),
+            ApiError::NotFound(id) => write!(f, "item {id} not found"),
+            ApiError::UserNotFound(username) => write!(f, "user {username} not found"),
+            ApiError::Timeout => write!(f, "request timed out"),
+            ApiError::RateLimited(_) => write!(f, "too many requests, try again later"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ApiError::Network(err) => Some(err),
+            ApiError::Deserialize(err) => Some(err),
+            ApiError::NotFound(_)
+            | ApiError::UserNotFound(_)
+            | ApiError::Timeout
+            | ApiError::RateLimited(_) => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            ApiError::Timeout
+        } else {
+            ApiError::Network(err)
+        }
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(err: serde_json::Error) -> Self {
+        ApiError::Deserialize(err)
+    }
+}
@@ -17,4 +17,9 @@
 // ----------------------------------------------------------------
 
 pub mod api;
+pub mod browser;
+pub mod error;
+pub mod render;
+pub mod storage;
 pub mod types;
+pub mod util;
@@ -18,55 +18,1133 @@
 
 #![allow(non_snake_case)]
 
-use chrono::Local;
+use std::collections::HashSet;
+use std::pin::pin;
+use std::rc::Rc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use dioxus::dioxus_core::CapturedError;
 use dioxus::prelude::*;
+use futures::future::{self, join_all, Either};
+use futures::StreamExt;
 
-use hackernews::api;
-use hackernews::types::{Comment, PreviewState, StoryItem};
+use hackernews::api::{self, ApiClient, FirebaseProvider, StoryProvider};
+use hackernews::browser;
+use hackernews::error::ApiError;
+use hackernews::render;
+use hackernews::storage;
+use hackernews::types::{
+    Comment, CommentDefault, Feed, Item, ItemType, Palette, PreviewState, ScoreHistory, Settings,
+    SortDirection, SortKey, StoryCache, StoryItem, Theme, User,
+};
+use hackernews::util;
 
 // ----------------------------------------------------------------
 
 // @see https://dioxuslabs.com/learn/0.5/guide/your_first_component
 
+/// How long the pointer has to rest on a story before it is previewed.
+const HOVER_DELAY: Duration = Duration::from_millis(200);
+
+/// Top-level comments the preview renders per "show more comments" click.
+const COMMENT_PAGE_SIZE: usize = 30;
+
+/// Characters of plain text after which a comment is cut short behind "read more".
+const LONG_COMMENT_LENGTH: usize = 800;
+
+/// How often the scores and comment counts behind the listing sparklines are sampled.
+const SCORE_SAMPLE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// How far the comments have to be scrolled before "back to top" shows up, in pixels.
+const BACK_TO_TOP_THRESHOLD: f64 = 600.0;
+
+/// How long "Copied!" stays next to a copy link.
+const COPIED_DURATION: Duration = Duration::from_secs(2);
+
+/// Characters of a self-post's text shown under its title.
+const EXCERPT_LENGTH: usize = 200;
+
+/// Stories printed by `--headless` when no count follows the flag.
+#[cfg(not(target_arch = "wasm32"))]
+const HEADLESS_STORY_COUNT: usize = 10;
+
 fn main() {
+    // Sends `api` request logs to the browser console.
+    #[cfg(target_arch = "wasm32")]
+    tracing_wasm::set_as_global_default();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(count) = headless_story_count(std::env::args().skip(1)) {
+        return headless(count);
+    }
+
     launch(App);
 }
 
+/// The story count of `--headless [N]`, or `None` to start the GUI.
+#[cfg(not(target_arch = "wasm32"))]
+fn headless_story_count(mut args: impl Iterator<Item = String>) -> Option<usize> {
+    args.position(|arg| arg == "--headless")?;
+    Some(
+        args.next()
+            .and_then(|count| count.parse().ok())
+            .unwrap_or(HEADLESS_STORY_COUNT),
+    )
+}
+
+/// Prints the top `count` stories as text, for scripts and cron jobs.
+#[cfg(not(target_arch = "wasm32"))]
+fn headless(count: usize) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to start the async runtime");
+
+    match runtime.block_on(api::get_stories(count)) {
+        Ok(stories) => print!("{}", render::to_text(&stories)),
+        Err(err) => {
+            eprintln!("couldn't fetch stories: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
 // ----------------------------------------------------------------
 
 fn App() -> Element {
-    use_context_provider(|| Signal::new(PreviewState::Unset));
+    let settings = use_persisted_settings();
+    let cache = use_persisted_cache();
+    use_context_provider(|| Signal::new(Theme::default()));
+    use_context_provider(|| Signal::new(OpenProfile::default()));
+    let provider = use_provider(settings);
+    let preview_state = use_persisted_preview(settings, cache, provider);
+    use_score_history(settings, preview_state, provider);
+    let palette = use_palette();
+
+    rsx! {
+        style { "body {{ margin: 0; background: {palette.background}; }} a {{ color: {palette.link}; }}" }
+        Router::<Route> {}
+        ProfilePanel {}
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Routable)]
+enum Route {
+    #[route("/")]
+    Home {},
+    /// Opens the split view with the story `id` already in the preview.
+    #[route("/item/:id")]
+    ItemPage { id: i64 },
+    #[route("/:..segments")]
+    NotFound { segments: Vec<String> },
+}
+
+#[component]
+fn Home() -> Element {
+    rsx! { SplitView { preview_open: false } }
+}
+
+#[component]
+fn ItemPage(id: i64) -> Element {
+    let palette = use_palette();
+    let cache = consume_context::<Signal<StoryCache>>();
+    let preview_state = consume_context::<Signal<PreviewState>>();
+    let settings = consume_context::<Signal<Settings>>();
+    let provider = consume_context::<Provider>();
+    let mut deep_linked = consume_context::<CopyValue<DeepLinked>>();
+    use_hook(move || deep_linked.set(DeepLinked(true)));
+
+    // Only stories, jobs and polls have a preview; anything else is "not found".
+    let item = use_resource(use_reactive((&id,), move |(id,)| async move {
+        let source = provider.read().clone();
+        let item = source.get_item(id).await?;
+        if matches!(item, Item::Story(_) | Item::Job(_) | Item::Poll(_)) {
+            // Spawned, so the resource doesn't subscribe to the cache it reads.
+            let fetch_depth = settings.peek().comment_fetch_depth;
+            spawn(preview_story(
+                provider,
+                cache,
+                preview_state,
+                id,
+                fetch_depth,
+            ));
+            Ok(true)
+        } else {
+            Ok::<_, ApiError>(false)
+        }
+    }));
+
+    match &*item.read_unchecked() {
+        Some(Ok(true)) => rsx! { SplitView { preview_open: true } },
+        Some(Ok(false)) | Some(Err(ApiError::NotFound(_))) => rsx! {
+            NotFound { segments: vec!["item".to_string(), id.to_string()] }
+        },
+        Some(Err(err)) => rsx! {
+            div { padding: "0.5rem", color: palette.text, "Couldn't load item {id}: {err}" }
+        },
+        None => rsx! { Spinner { label: "Loading item {id}..." } },
+    }
+}
+
+#[component]
+fn NotFound(segments: Vec<String>) -> Element {
+    let palette = use_palette();
+    let path = segments.join("/");
+
     rsx! {
+        div {
+            padding: "0.5rem",
+            color: palette.text,
+            h2 { "Not found" }
+            p { "There is no story at /{path}." }
+            Link { to: Route::Home {}, "Back to the front page" }
+        }
+    }
+}
+
+/// Below 768px wide the list takes the full width and the preview
+/// becomes a full-screen overlay, shown only while `.open`.
+const LAYOUT_CSS: &str = "
+.split-list, .split-preview { width: 50%; }
+@media (max-width: 767px) {
+    .split-list { width: 100%; }
+    .split-preview { display: none; }
+    .split-preview.open { display: block; position: fixed; inset: 0; width: auto; overflow-y: auto; z-index: 10; }
+}
+";
+
+/// The story list next to the preview of the hovered story; on narrow screens
+/// the preview opens over the list instead, starting open if `preview_open`.
+#[component]
+fn SplitView(preview_open: bool) -> Element {
+    let palette = use_palette();
+    let open = use_context_provider(|| Signal::new(PreviewOpen(preview_open)));
+    let preview_state = consume_context::<Signal<PreviewState>>();
+
+    // On the document rather than this div: after hovering a story, nothing
+    // inside the view has focus.
+    use_future(move || async move {
+        let mut escapes = browser::key_presses("Escape");
+        while escapes.recv().await.is_ok() {
+            close_preview(preview_state, open);
+        }
+    });
+
+    rsx! {
+        style { {LAYOUT_CSS} }
         div {
             display: "flex",
             flex_direction: "row",
             width: "100%",
-            div { width: "50%", Stories {} }
-            div { width: "50%", Preview {} }
+            min_height: "100vh",
+            background: palette.background,
+            color: palette.text,
+            div {
+                class: "split-list",
+                ErrorBoundary {
+                    handle_error: fallback("Couldn't render the story list"),
+                    Stories {}
+                }
+            }
+            div {
+                class: if open().0 { "split-preview open" } else { "split-preview" },
+                background: palette.background,
+                role: "region",
+                aria_label: "Story preview",
+                aria_live: "polite",
+                ErrorBoundary {
+                    handle_error: fallback("Couldn't render the preview"),
+                    Preview {}
+                }
+            }
         }
     }
 }
 
+/// Where stories come from; a context so components can run against a stub.
+/// Held in a `CopyValue` so event handlers can capture it like a signal.
+type Provider = CopyValue<Rc<dyn StoryProvider>>;
+
+/// Previews `story_id` through the app's `StoryProvider`.
+async fn preview_story(
+    provider: Provider,
+    cache: Signal<StoryCache>,
+    preview_state: Signal<PreviewState>,
+    story_id: i64,
+    max_depth: usize,
+) {
+    let provider = provider.read().clone();
+    api::resolve_story_with(&*provider, cache, preview_state, story_id, max_depth).await
+}
+
+/// Asks `Stories` to preview a story, in a task of its own rather than the
+/// caller's.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PreviewRequest(i64);
+
+/// An `ErrorBoundary` handler that shows `message` in place of whatever
+/// failed, leaving the rest of the page alone.
+fn fallback(message: &'static str) -> impl Fn(CapturedError) -> Element {
+    move |_error| {
+        let palette = use_palette();
+        rsx! {
+            div {
+                padding: "0.5rem",
+                color: palette.muted,
+                font_style: "italic",
+                "{message}"
+            }
+        }
+    }
+}
+
+/// A story list kept in `Settings` rather than fetched from a feed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SavedList {
+    Starred,
+    ReadLater,
+}
+
+impl SavedList {
+    const ALL: [SavedList; 2] = [SavedList::Starred, SavedList::ReadLater];
+
+    fn label(self) -> &'static str {
+        match self {
+            SavedList::Starred => "starred",
+            SavedList::ReadLater => "read later",
+        }
+    }
+
+    /// The ids to list: newest stars first, the read-later queue in order.
+    fn ids(self, settings: &Settings) -> Vec<i64> {
+        match self {
+            SavedList::Starred => settings.favorites.iter().rev().copied().collect(),
+            SavedList::ReadLater => settings.read_later.clone(),
+        }
+    }
+
+    fn contains(self, settings: &Settings, id: i64) -> bool {
+        match self {
+            SavedList::Starred => settings.is_favorite(id),
+            SavedList::ReadLater => settings.is_read_later(id),
+        }
+    }
+}
+
+/// The search over the comments of the open story; empty shows them all.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct CommentQuery(String);
+
+/// The last "collapse all" or "expand all" of the preview. Comments apply it
+/// whenever `generation` moves past the one they were mounted at.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct CollapseAll {
+    generation: u64,
+    collapsed: bool,
+}
+
+/// Empties the preview and closes its overlay.
+fn close_preview(mut preview_state: Signal<PreviewState>, mut open: Signal<PreviewOpen>) {
+    preview_state.set(PreviewState::Unset);
+    open.set(PreviewOpen(false));
+}
+
+/// Whether the preview overlay is open; only matters on narrow screens, where
+/// the preview doesn't fit next to the list.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct PreviewOpen(bool);
+
+/// Set as soon as `/item/:id` mounts, so the preview restored from the last
+/// visit doesn't replace the story the link asked for.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct DeepLinked(bool);
+
+/// The author whose profile panel is open, if any.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct OpenProfile(Option<String>);
+
+/// The colors of the current `Theme`.
+fn use_palette() -> Palette {
+    consume_context::<Signal<Theme>>().read().palette()
+}
+
+/// Provides the `Settings` context, restored from local storage on start and
+/// saved back whenever it changes.
+fn use_persisted_settings() -> Signal<Settings> {
+    let mut settings = use_context_provider(|| Signal::new(Settings::default()));
+    let mut restored = use_signal(|| false);
+
+    use_future(move || async move {
+        if let Some(stored) = storage::load::<Settings>(storage::SETTINGS_KEY).await {
+            settings.set(stored);
+        }
+        restored.set(true);
+    });
+
+    use_effect(move || {
+        let settings = settings.read();
+        // Saving before the restore finished would overwrite the stored settings.
+        if restored() {
+            storage::save(storage::SETTINGS_KEY, &*settings);
+        }
+    });
+
+    settings
+}
+
+/// Provides the `Provider` context unless a parent already did, e.g. with a
+/// stub. The Firebase default is rebuilt whenever the concurrency limit in
+/// `settings` changes so in-flight requests stay bounded by it.
+fn use_provider(settings: Signal<Settings>) -> Provider {
+    let (mut provider, injected) = use_hook(|| match try_consume_context::<Provider>() {
+        Some(provider) => (provider, true),
+        None => (
+            provide_context(Provider::new(firebase_provider(
+                api::MAX_CONCURRENT_REQUESTS,
+            ))),
+            false,
+        ),
+    });
+    let limit = use_memo(move || settings.read().max_concurrent_requests);
+
+    use_effect(move || {
+        let limit = limit();
+        if !injected {
+            provider.set(firebase_provider(limit));
+        }
+    });
+
+    provider
+}
+
+fn firebase_provider(max_concurrent_requests: usize) -> Rc<dyn StoryProvider> {
+    let client = ApiClient::default().with_max_concurrent_requests(max_concurrent_requests);
+    Rc::new(FirebaseProvider::new(client))
+}
+
+/// Provides the `StoryCache` context, persisted like the settings so resolved
+/// stories can still be previewed offline.
+fn use_persisted_cache() -> Signal<StoryCache> {
+    let mut cache = use_context_provider(|| Signal::new(StoryCache::default()));
+    let mut restored = use_signal(|| false);
+
+    use_future(move || async move {
+        if let Some(stored) = storage::load::<StoryCache>(storage::STORY_CACHE_KEY).await {
+            cache.set(stored);
+        }
+        restored.set(true);
+    });
+
+    use_effect(move || {
+        let cache = cache.read();
+        if restored() {
+            storage::save(storage::STORY_CACHE_KEY, &*cache);
+        }
+    });
+
+    cache
+}
+
+/// Provides the `PreviewState` context, reopening the story that was open on
+/// the last visit. Only the id is stored, so a reload never restores a spinner.
+fn use_persisted_preview(
+    settings: Signal<Settings>,
+    cache: Signal<StoryCache>,
+    provider: Provider,
+) -> Signal<PreviewState> {
+    let preview_state = use_context_provider(|| Signal::new(PreviewState::Unset));
+    let deep_linked = use_context_provider(|| CopyValue::new(DeepLinked::default()));
+    let mut restored = use_signal(|| false);
+
+    use_future(move || async move {
+        let stored = storage::load::<Option<i64>>(storage::PREVIEW_KEY)
+            .await
+            .flatten();
+        // `/item/:id` fetches its story over the network, so it has usually not
+        // filled the preview yet; it only marks that it is going to.
+        let untouched =
+            matches!(*preview_state.peek(), PreviewState::Unset) && !deep_linked.read().0;
+        restored.set(true);
+        if let (Some(story_id), true) = (stored, untouched) {
+            let fetch_depth = settings.peek().comment_fetch_depth;
+            preview_story(provider, cache, preview_state, story_id, fetch_depth).await;
+        }
+    });
+
+    use_effect(move || {
+        let preview_state = preview_state.read();
+        // Loading is passing; keep whatever was stored until it settles.
+        if restored() && !matches!(*preview_state, PreviewState::Loading) {
+            storage::save(storage::PREVIEW_KEY, &preview_state.story_id());
+        }
+    });
+
+    preview_state
+}
+
+/// Provides the `ScoreHistory` context, sampling the scores and comment counts of starred stories
+/// and the previewed one every [`SCORE_SAMPLE_INTERVAL`] while the app is open.
+fn use_score_history(
+    settings: Signal<Settings>,
+    preview_state: Signal<PreviewState>,
+    provider: Provider,
+) -> Signal<ScoreHistory> {
+    let mut history = use_context_provider(|| Signal::new(ScoreHistory::default()));
+
+    use_future(move || async move {
+        loop {
+            let mut ids = settings.peek().favorites.clone();
+            ids.extend(preview_state.peek().story_id());
+            ids.sort_unstable();
+            ids.dedup();
+
+            let source = provider.read().clone();
+            let sampled = join_all(ids.into_iter().map(|id| source.get_story_preview(id))).await;
+            let now = Utc::now();
+            let mut history = history.write();
+            for story in sampled.into_iter().flatten() {
+                history.record(story.id, now, story.score, story.descendants);
+            }
+            drop(history);
+
+            tokio::time::sleep(SCORE_SAMPLE_INTERVAL).await;
+        }
+    });
+
+    history
+}
+
 // ----------------------------------------------------------------
 
 fn Stories() -> Element {
-    let stories = use_resource(move || api::get_stories(10));
+    let palette = use_palette();
+    let mut settings = consume_context::<Signal<Settings>>();
+    let mut theme = consume_context::<Signal<Theme>>();
+    let mut feed = use_signal(Feed::default);
+    // Set while showing one of the lists kept in settings instead of `feed`.
+    let mut saved = use_signal(|| None::<SavedList>);
+    let mut stories = use_signal(Vec::<StoryItem>::new);
+    let mut offset = use_signal(|| 0);
+    let mut loading = use_signal(|| false);
+    let mut loading_more = use_signal(|| false);
+    let mut query = use_signal(String::new);
+    let mut sort_key = use_signal(|| None::<SortKey>);
+    let mut sort_direction = use_signal(SortDirection::default);
+    let mut selected = use_signal(|| None::<usize>);
+    let mut failed = use_signal(|| 0);
+    let mut offline = use_signal(|| false);
+    let mut refresh_in_place = use_signal(|| false);
+    // Set by a manual or automatic refresh, so only those announce new stories.
+    let mut refreshing = use_signal(|| false);
+    let mut new_ids = use_signal(Vec::<i64>::new);
+    let cache = consume_context::<Signal<StoryCache>>();
+    let preview_state = consume_context::<Signal<PreviewState>>();
+    let provider = consume_context::<Provider>();
+
+    // Only re-fetch when the count changes, not on every settings write.
+    let story_count = use_memo(move || settings.read().story_count);
+
+    // The id list is fetched once per feed; pages are sliced from it by `offset`.
+    let mut story_ids = use_resource(move || {
+        let feed = feed();
+        let count = story_count();
+        // Auto-refreshes reload everything already on screen, so the list doesn't shrink.
+        let in_place = refresh_in_place.replace(false);
+        let refresh = refreshing.replace(false);
+        let count = if in_place {
+            count.max(*offset.peek())
+        } else {
+            count
+        };
+        let saved_ids = saved().map(|saved| saved.ids(&settings.peek()));
+        let from_settings = saved_ids.is_some();
+        let provider = provider.read().clone();
+        async move {
+            loading.set(true);
+            let fetched = async {
+                let ids = match saved_ids {
+                    Some(ids) => ids,
+                    None => provider.get_story_ids(feed).await?,
+                };
+                let first_page = provider.get_stories_batch(&ids, 0, count).await;
+                Ok::<_, ApiError>((ids, first_page))
+            }
+            .await;
+            loading.set(false);
+            if !in_place {
+                selected.set(None);
+            }
+
+            let (ids, first_page) = match fetched {
+                Ok(fetched) => fetched,
+                // Fall back to the last list we saw, if there is one.
+                Err(err) => {
+                    let cached = if from_settings {
+                        None
+                    } else {
+                        storage::load::<Vec<StoryItem>>(&storage::stories_key(feed)).await
+                    };
+                    let Some(cached) = cached else {
+                        return Err(err);
+                    };
+                    let ids = cached.iter().map(|story| story.id).collect::<Vec<_>>();
+                    offset.set(ids.len());
+                    failed.set(0);
+                    stories.set(cached);
+                    offline.set(true);
+                    return Ok(ids);
+                }
+            };
+            if !from_settings {
+                storage::save(&storage::stories_key(feed), &first_page.stories);
+            }
+            // Compared against everything on screen, pages loaded with "more" included.
+            let fresh = if refresh && !from_settings {
+                let shown: HashSet<i64> = stories.peek().iter().map(|story| story.id).collect();
+                first_page
+                    .stories
+                    .iter()
+                    .map(|story| story.id)
+                    .filter(|id| !shown.contains(id))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            new_ids.set(fresh);
+            offline.set(false);
+            stories.set(first_page.stories);
+            failed.set(first_page.failures.len());
+            offset.set(first_page.next_offset);
+            Ok(ids)
+        }
+    });
+
+    // Rows are keyed by story id, so a refresh updates them in place rather
+    // than rebuilding the list under the reader. Each change of the interval
+    // starts the wait over, so a shorter or disabled one applies right away.
+    let refresh_interval = use_memo(move || settings.read().refresh_interval);
+    let auto_refresh = use_coroutine(move |mut changes: UnboundedReceiver<()>| async move {
+        loop {
+            let minutes = *refresh_interval.peek();
+            let wait = async move {
+                // Disabled until the setting changes.
+                if minutes == 0 {
+                    future::pending::<()>().await;
+                }
+                tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+            };
+            match future::select(pin!(wait), changes.next()).await {
+                Either::Left(_) => {
+                    if !*loading.peek() {
+                        refresh_in_place.set(true);
+                        refreshing.set(true);
+                        story_ids.restart();
+                    }
+                }
+                Either::Right((Some(()), _)) => {}
+                Either::Right((None, _)) => return,
+            }
+        }
+    });
+    use_effect(move || {
+        refresh_interval();
+        auto_refresh.send(());
+    });
 
-    match &*stories.read_unchecked() {
-        Some(Ok(list)) => {
+    // Previews of the listings are fetched here, so one that has started survives
+    // the pointer moving on and the listing being filtered out or unmounted.
+    use_coroutine(
+        move |mut requests: UnboundedReceiver<PreviewRequest>| async move {
+            while let Some(PreviewRequest(id)) = requests.next().await {
+                let fetch_depth = settings.peek().comment_fetch_depth;
+                spawn(preview_story(
+                    provider,
+                    cache,
+                    preview_state,
+                    id,
+                    fetch_depth,
+                ));
+            }
+        },
+    );
+
+    // Ranks are taken before filtering and sorting so they keep matching the feed.
+    let mut visible: Vec<(usize, StoryItem)> = stories
+        .read()
+        .iter()
+        .cloned()
+        .enumerate()
+        .filter(|(_, story)| story.title_matches(&query.read()))
+        .filter(|(_, story)| saved().is_none_or(|saved| saved.contains(&settings.read(), story.id)))
+        .filter(|(_, story)| !settings.read().is_hidden(story.id))
+        .filter(|(_, story)| !settings.read().is_title_muted(&story.display_title()))
+        .filter(|(_, story)| {
+            story
+                .host()
+                .is_none_or(|host| !settings.read().is_domain_muted(&host))
+        })
+        .collect();
+    // Counted separately so the slider can say how much it is hiding.
+    let min_score = settings.read().min_score;
+    let below_threshold = visible
+        .iter()
+        .filter(|(_, story)| story.score < min_score)
+        .count();
+    visible.retain(|(_, story)| story.score >= min_score);
+    if let Some(key) = sort_key() {
+        visible.sort_by(|(_, a), (_, b)| key.compare(a, b, sort_direction()));
+    }
+
+    // What `j`/`k` step through and `Enter`/`o` opens, in display order.
+    let strip_tracking_params = settings.read().strip_tracking_params;
+    let targets: Vec<(i64, String)> = visible
+        .iter()
+        .map(|(_, story)| {
+            let url = match story.url.as_deref().filter(|url| !url.is_empty()) {
+                Some(url) if strip_tracking_params => util::clean_url(url),
+                Some(url) => url.to_string(),
+                None => util::hn_item_url(story.id),
+            };
+            (story.id, url)
+        })
+        .collect();
+    let current = selected().filter(|&index| index < targets.len());
+
+    let list = match &*story_ids.read_unchecked() {
+        Some(Ok(ids)) => {
+            let has_more = offset() < ids.len();
+            let ids = ids.clone();
             rsx! {
                 div {
-                    for story in list {
-                        StoryListing { story: story.clone() }
+                    role: "list",
+                    aria_label: "Stories",
+                    for (position, (index, story)) in visible.into_iter().enumerate() {
+                        // Jobs have no score or discussion worth a metadata row.
+                        if story.item_type() == ItemType::Job {
+                            JobListing {
+                                key: "{story.id}",
+                                story,
+                                rank: index + 1,
+                                selected: current == Some(position),
+                            }
+                        } else {
+                            StoryListing {
+                                key: "{story.id}",
+                                story,
+                                rank: index + 1,
+                                selected: current == Some(position),
+                            }
+                        }
+                    }
+                }
+                if failed() > 0 {
+                    div {
+                        padding: "0.5rem",
+                        color: palette.muted,
+                        if failed() == 1 { "1 story couldn't be loaded" } else { "{failed} stories couldn't be loaded" }
+                    }
+                }
+                if has_more {
+                    button {
+                        margin: "0.5rem",
+                        disabled: loading_more(),
+                        onclick: move |_event| {
+                            let ids = ids.clone();
+                            let count = settings.read().story_count;
+                            let start = offset();
+                            let current_feed = (feed(), saved());
+                            let provider = provider.read().clone();
+                            loading_more.set(true);
+                            spawn(async move {
+                                let page = provider.get_stories_batch(&ids, start, count).await;
+                                // Drop the page if the feed was switched while it was loading.
+                                if (feed(), saved()) == current_feed {
+                                    stories.write().extend(page.stories);
+                                    failed += page.failures.len();
+                                    offset.set(page.next_offset);
+                                }
+                                loading_more.set(false);
+                            });
+                        },
+                        if loading_more() { "Loading..." } else { "Load more" }
                     }
                 }
             }
         }
         Some(Err(err)) => {
-            rsx! {"An error occurred while fetching stories {err}"}
+            let retryable = matches!(
+                err,
+                ApiError::Network(_) | ApiError::Timeout | ApiError::RateLimited(_)
+            );
+            rsx! {
+                "An error occurred while fetching stories {err}"
+                if retryable {
+                    button {
+                        margin_left: "0.5rem",
+                        onclick: move |_event| story_ids.restart(),
+                        "Retry"
+                    }
+                }
+            }
         }
         None => {
-            rsx! {"Loading items"}
+            rsx! { Spinner { label: "Loading items" } }
+        }
+    };
+
+    rsx! {
+        div {
+            tabindex: "0",
+            outline: "none",
+            aria_label: "Story list: j and k move the selection, Enter opens it, Escape closes the preview",
+            onkeydown: move |event: KeyboardEvent| match event.key() {
+                Key::Character(key) if key == "j" || key == "k" => {
+                    if targets.is_empty() {
+                        return;
+                    }
+                    let index = match current {
+                        None => 0,
+                        Some(index) if key == "j" => (index + 1).min(targets.len() - 1),
+                        Some(index) => index.saturating_sub(1),
+                    };
+                    selected.set(Some(index));
+                    let (id, _) = targets[index];
+                    browser::scroll_into_view(&format!("story-{id}"));
+                    let fetch_depth = settings.peek().comment_fetch_depth;
+                    spawn(preview_story(provider, cache, preview_state, id, fetch_depth));
+                }
+                // Escape also closes the preview, from `SplitView`.
+                Key::Escape => selected.set(None),
+                key if key == Key::Enter || key == Key::Character("o".to_string()) => {
+                    if let Some(index) = current {
+                        let (id, url) = &targets[index];
+                        settings.write().mark_visited(*id);
+                        browser::open(url);
+                    }
+                }
+                _ => {}
+            },
+            div {
+                padding: "0.5rem",
+                input {
+                    width: "100%",
+                    box_sizing: "border-box",
+                    r#type: "search",
+                    placeholder: "Filter loaded stories by title",
+                    value: "{query}",
+                    oninput: move |event| query.set(event.value()),
+                    // Typing into the filter shouldn't move the selection.
+                    onkeydown: move |event| event.stop_propagation(),
+                }
+            }
+            div {
+                padding: "0.5rem",
+                color: palette.muted,
+                for (index, item) in Feed::ALL.into_iter().enumerate() {
+                    if index > 0 { " | " }
+                    a {
+                        href: "#",
+                        font_weight: if saved().is_none() && feed() == item { "bold" } else { "normal" },
+                        prevent_default: "onclick",
+                        onclick: move |_event| {
+                            feed.set(item);
+                            saved.set(None);
+                        },
+                        "{item.label()}"
+                    }
+                }
+                " | "
+                for (index, list) in SavedList::ALL.into_iter().enumerate() {
+                    if index > 0 { " | " }
+                    a {
+                        href: "#",
+                        font_weight: if saved() == Some(list) { "bold" } else { "normal" },
+                        prevent_default: "onclick",
+                        onclick: move |_event| saved.set(Some(list)),
+                        "{list.label()}"
+                    }
+                }
+                select {
+                    margin_left: "1rem",
+                    value: "{settings.read().story_count}",
+                    onchange: move |event| {
+                        if let Ok(count) = event.value().parse() {
+                            settings.write().story_count = count;
+                        }
+                    },
+                    for count in Settings::STORY_COUNTS {
+                        option {
+                            value: "{count}",
+                            selected: settings.read().story_count == count,
+                            "{count} stories"
+                        }
+                    }
+                }
+                select {
+                    margin_left: "0.5rem",
+                    title: "Auto-refresh",
+                    value: "{settings.read().refresh_interval}",
+                    onchange: move |event| {
+                        if let Ok(minutes) = event.value().parse() {
+                            settings.write().refresh_interval = minutes;
+                        }
+                    },
+                    for minutes in Settings::REFRESH_INTERVALS {
+                        option {
+                            value: "{minutes}",
+                            selected: settings.read().refresh_interval == minutes,
+                            if minutes == 0 { "no auto-refresh" } else { "refresh every {minutes} min" }
+                        }
+                    }
+                }
+                button {
+                    margin_left: "0.5rem",
+                    onclick: move |_event| {
+                        let toggled = settings.read().density.toggled();
+                        settings.write().density = toggled;
+                    },
+                    "{settings.read().density.label()}"
+                }
+                button {
+                    margin_left: "0.5rem",
+                    title: "Show times relative to now or as exact dates",
+                    onclick: move |_event| {
+                        let toggled = settings.read().time_format.toggled();
+                        settings.write().time_format = toggled;
+                    },
+                    "times: {settings.read().time_format.label()}"
+                }
+                button {
+                    margin_left: "0.5rem",
+                    title: "Strip utm_* and similar tracking parameters from story links",
+                    onclick: move |_event| {
+                        let strip = !settings.read().strip_tracking_params;
+                        settings.write().strip_tracking_params = strip;
+                    },
+                    if settings.read().strip_tracking_params { "trackers: stripped" } else { "trackers: kept" }
+                }
+                button {
+                    margin_left: "0.5rem",
+                    title: "How comments start out in new previews",
+                    onclick: move |_event| {
+                        let toggled = settings.read().default_comment_state.toggled();
+                        settings.write().default_comment_state = toggled;
+                    },
+                    "comments: {settings.read().default_comment_state.label()}"
+                }
+                if !settings.read().hidden.is_empty() {
+                    button {
+                        margin_left: "0.5rem",
+                        onclick: move |_event| settings.write().hidden.clear(),
+                        "unhide all ({settings.read().hidden.len()})"
+                    }
+                }
+                button {
+                    margin_left: "0.5rem",
+                    onclick: move |_event| theme.set(theme().toggled()),
+                    if theme() == Theme::Dark { "light mode" } else { "dark mode" }
+                }
+                button {
+                    margin_left: "0.5rem",
+                    disabled: loading(),
+                    onclick: move |_event| {
+                        refreshing.set(true);
+                        story_ids.restart();
+                    },
+                    "refresh"
+                }
+                button {
+                    margin_left: "0.5rem",
+                    title: "Preview a random story from the whole feed",
+                    // Picks from every id in the feed, not only the loaded page.
+                    disabled: !matches!(*story_ids.read(), Some(Ok(ref ids)) if !ids.is_empty()),
+                    onclick: move |_event| {
+                        let ids = match &*story_ids.read() {
+                            Some(Ok(ids)) => ids.clone(),
+                            _ => return,
+                        };
+                        let fetch_depth = settings.read().comment_fetch_depth;
+                        spawn(async move {
+                            let Some(random) = browser::random().await else {
+                                return;
+                            };
+                            let index = ((random * ids.len() as f64) as usize).min(ids.len() - 1);
+                            preview_story(provider, cache, preview_state, ids[index], fetch_depth).await;
+                        });
+                    },
+                    "random"
+                }
+                button {
+                    margin_left: "0.5rem",
+                    title: "Download the loaded stories as an RSS feed",
+                    onclick: move |_event| {
+                        let rss = render::to_rss(&stories.read());
+                        browser::download("hackernews.rss", "application/rss+xml", &rss);
+                    },
+                    "rss"
+                }
+            }
+            if offline() {
+                div {
+                    padding: "0.5rem",
+                    color: palette.muted,
+                    font_style: "italic",
+                    "Offline — showing cached data"
+                }
+            }
+            if let Some(&first_new) = new_ids.read().first() {
+                div {
+                    padding: "0.5rem",
+                    background: palette.highlight,
+                    cursor: "pointer",
+                    onclick: move |_event| {
+                        browser::scroll_into_view(&format!("story-{first_new}"));
+                        new_ids.set(Vec::new());
+                    },
+                    if new_ids.read().len() == 1 { "1 new story" } else { "{new_ids.read().len()} new stories" }
+                }
+            }
+            MutedDomains {}
+            MutedKeywords {}
+            // Keep the current list on screen while a refresh is in flight.
+            if loading() && story_ids.read().is_some() {
+                Spinner { label: "Refreshing..." }
+            }
+            div {
+                padding: "0 0.5rem",
+                color: palette.muted,
+                "sort: "
+                a {
+                    href: "#",
+                    font_weight: if sort_key().is_none() { "bold" } else { "normal" },
+                    prevent_default: "onclick",
+                    onclick: move |_event| sort_key.set(None),
+                    "rank"
+                }
+                for key in SortKey::ALL {
+                    " | "
+                    a {
+                        href: "#",
+                        font_weight: if sort_key() == Some(key) { "bold" } else { "normal" },
+                        prevent_default: "onclick",
+                        onclick: move |_event| sort_key.set(Some(key)),
+                        "{key.label()}"
+                    }
+                }
+                button {
+                    margin_left: "0.5rem",
+                    disabled: sort_key().is_none(),
+                    onclick: move |_event| sort_direction.set(sort_direction().toggled()),
+                    if sort_direction() == SortDirection::Descending { "desc" } else { "asc" }
+                }
+            }
+            div {
+                padding: "0 0.5rem",
+                color: palette.muted,
+                display: "flex",
+                align_items: "center",
+                "min score: "
+                input {
+                    r#type: "range",
+                    min: "0",
+                    max: "500",
+                    step: "10",
+                    value: "{min_score}",
+                    oninput: move |event| {
+                        if let Ok(score) = event.value().parse() {
+                            settings.write().min_score = score;
+                        }
+                    },
+                }
+                span { padding_left: "0.5rem", "{min_score}" }
+                if below_threshold > 0 {
+                    span { padding_left: "0.5rem", "({below_threshold} hidden)" }
+                }
+            }
+            {list}
+        }
+    }
+}
+
+/// Lists the muted sites, each with a way to unmute it.
+fn MutedDomains() -> Element {
+    let palette = use_palette();
+    let mut settings = consume_context::<Signal<Settings>>();
+
+    let mut domains: Vec<String> = settings.read().muted_domains.iter().cloned().collect();
+    if domains.is_empty() {
+        return None;
+    }
+    domains.sort();
+
+    rsx! {
+        details {
+            padding: "0 0.5rem",
+            color: palette.muted,
+            summary { "muted sites ({domains.len()})" }
+            for domain in domains {
+                div {
+                    key: "{domain}",
+                    padding_left: "1rem",
+                    "{domain} "
+                    a {
+                        href: "#",
+                        prevent_default: "onclick",
+                        onclick: move |_event| {
+                            settings.write().muted_domains.remove(&domain);
+                        },
+                        "unmute"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Lists the muted title keywords, with a field to add more.
+fn MutedKeywords() -> Element {
+    let palette = use_palette();
+    let mut settings = consume_context::<Signal<Settings>>();
+    let mut draft = use_signal(String::new);
+
+    let keywords = settings.read().muted_keywords.clone();
+
+    rsx! {
+        details {
+            padding: "0 0.5rem",
+            color: palette.muted,
+            summary { "muted words ({keywords.len()})" }
+            for (index, keyword) in keywords.into_iter().enumerate() {
+                div {
+                    key: "{keyword}",
+                    padding_left: "1rem",
+                    "{keyword} "
+                    a {
+                        href: "#",
+                        prevent_default: "onclick",
+                        onclick: move |_event| {
+                            settings.write().muted_keywords.remove(index);
+                        },
+                        "unmute"
+                    }
+                }
+            }
+            form {
+                padding_left: "1rem",
+                prevent_default: "onsubmit",
+                onsubmit: move |_event| {
+                    settings.write().mute_keyword(&draft.read());
+                    draft.set(String::new());
+                },
+                input {
+                    placeholder: "Hide titles containing...",
+                    value: "{draft}",
+                    oninput: move |event| draft.set(event.value()),
+                    onkeydown: move |event| event.stop_propagation(),
+                }
+                button { r#type: "submit", margin_left: "0.5rem", "mute" }
+            }
         }
     }
 }
@@ -74,30 +1152,256 @@ fn Stories() -> Element {
 // ----------------------------------------------------------------
 
 fn Preview() -> Element {
+    let palette = use_palette();
     let preview_state = consume_context::<Signal<PreviewState>>();
+    let settings = consume_context::<Signal<Settings>>();
+    let cache = consume_context::<Signal<StoryCache>>();
+    let provider = consume_context::<Provider>();
+    let mut exporting = use_signal(|| false);
+    // Why the last "Download JSON" failed, until it is retried.
+    let mut export_error = use_signal(|| None::<ApiError>);
+    let mut shared = use_signal(|| false);
+    let mut comment_query = use_context_provider(|| Signal::new(CommentQuery::default()));
+    let mut collapse_all = use_context_provider(|| Signal::new(CollapseAll::default()));
+    let mut set_all_collapsed = move |collapsed: bool| {
+        let generation = collapse_all.peek().generation + 1;
+        collapse_all.set(CollapseAll {
+            generation,
+            collapsed,
+        });
+    };
+    // Top-level comments rendered so far; big threads render a page at a time.
+    let mut comment_limit = use_signal(|| COMMENT_PAGE_SIZE);
+
+    // Each story starts with a fresh search and its first page of comments.
+    let open_story = use_memo(move || preview_state.read().story_id());
+    use_effect(move || {
+        open_story();
+        comment_query.set(CommentQuery::default());
+        comment_limit.set(COMMENT_PAGE_SIZE);
+        export_error.set(None);
+    });
 
     match preview_state() {
         PreviewState::Unset => rsx! { "Hover over a story to preview it here" },
-        PreviewState::Loading => rsx! { "Loading..." },
+        PreviewState::Loading => rsx! {
+            ClosePreview {}
+            Spinner { label: "Loading..." }
+        },
+        PreviewState::Failed {
+            story_id,
+            timed_out,
+        } => {
+            rsx! {
+                ClosePreview {}
+                div {
+                    padding: "0.5rem",
+                    if timed_out { "Request timed out — " } else { "Couldn't load this story — " }
+                    a {
+                        href: "#",
+                        prevent_default: "onclick",
+                        onclick: move |_event| {
+                            let fetch_depth = settings.read().comment_fetch_depth;
+                            preview_story(provider, cache, preview_state, story_id, fetch_depth)
+                        },
+                        "retry?"
+                    }
+                }
+            }
+        }
         PreviewState::Loaded(story) => {
+            let story_id = story.item.id;
+            let score = util::points_label(story.item.score);
+            let comments = util::comments_label(story.item.descendants);
+            let top_border = format!("3px solid {}", palette.accent);
+            let share_text = story.item.share_text();
+            let hidden_comments = story.hidden_comment_count();
+            let mut export = move || {
+                exporting.set(true);
+                export_error.set(None);
+                // Through the provider, so its request limit covers the whole thread.
+                let source = provider.read().clone();
+                spawn(async move {
+                    match source.export_story_json(story_id).await {
+                        Ok(json) => {
+                            let filename = format!("hn-{story_id}.json");
+                            browser::download(&filename, "application/json", &json);
+                        }
+                        Err(err) => export_error.set(Some(err)),
+                    }
+                    exporting.set(false);
+                });
+            };
+            let CommentQuery(query) = comment_query();
+            let remaining_comments = story
+                .comments
+                .iter()
+                .filter(|comment| comment.thread_matches(&query))
+                .count()
+                .saturating_sub(comment_limit());
+            let matches: usize = story
+                .comments
+                .iter()
+                .map(|comment| comment.match_count(&query))
+                .sum();
+            // Link-only stories have nothing to read here.
+            let reading_minutes = story
+                .item
+                .text
+                .as_deref()
+                .filter(|text| !text.trim().is_empty())
+                .map(util::reading_minutes);
+            // The header stays put while the comments scroll on their own, and the
+            // whole pane sticks to the viewport while the story list scrolls.
             rsx! {
                 div {
+                    position: "sticky",
+                    top: "0",
+                    display: "flex",
+                    flex_direction: "column",
+                    max_height: "100vh",
+                    box_sizing: "border-box",
                     padding: "0.5rem",
                     div {
                         font_size: "1.5rem",
+                        ClosePreview {}
                         a {
                             href: story.item.url,
-                            "{story.item.title}"
+                            "{story.item.display_title()}"
+                        }
+                    }
+                    div {
+                        color: palette.muted,
+                        padding_bottom: "0.5rem",
+                        "{score} by "
+                        UserLink { username: story.item.by.clone() }
+                        " · "
+                        Timestamp { time: story.item.time }
+                        " · {comments}"
+                        if hidden_comments > 0 {
+                            span {
+                                title: "Deleted, flagged or unreachable",
+                                if hidden_comments == 1 { " (1 hidden)" } else { " ({hidden_comments} hidden)" }
+                            }
+                        }
+                        if let Some(minutes) = reading_minutes {
+                            " · ~{minutes} min read"
+                        }
+                    }
+                    div {
+                        button {
+                            disabled: exporting(),
+                            onclick: move |_event| export(),
+                            if exporting() { "Exporting..." } else { "Download JSON" }
+                        }
+                        if let Some(err) = &*export_error.read() {
+                            span {
+                                color: palette.muted,
+                                padding_left: "0.5rem",
+                                "Couldn't export: {err} — "
+                                a {
+                                    href: "#",
+                                    prevent_default: "onclick",
+                                    onclick: move |_event| export(),
+                                    "retry?"
+                                }
+                            }
+                        }
+                        button {
+                            margin_left: "0.5rem",
+                            onclick: move |_event| {
+                                let text = share_text.clone();
+                                spawn(async move {
+                                    if browser::share(&text).await || browser::copy_to_clipboard(&text).await {
+                                        shared.set(true);
+                                        tokio::time::sleep(COPIED_DURATION).await;
+                                        shared.set(false);
+                                    }
+                                });
+                            },
+                            if shared() { "Shared!" } else { "Share" }
+                        }
+                        if !story.comments.is_empty() {
+                            button {
+                                margin_left: "0.5rem",
+                                onclick: move |_event| set_all_collapsed(true),
+                                "Collapse all"
+                            }
+                            button {
+                                margin_left: "0.5rem",
+                                onclick: move |_event| set_all_collapsed(false),
+                                "Expand all"
+                            }
                         }
                     }
 
+                    if let Some(url) = story.item.url.clone() {
+                        LinkCard { url }
+                    }
+
                     div {
-                        dangerous_inner_html: story.item.text
+                        dangerous_inner_html: story.item.text.as_deref().map(util::sanitize_html)
+                    }
+
+                    for option in &story.poll_options {
+                        div {
+                            padding: "0.25rem 0.5rem",
+                            span { dangerous_inner_html: util::sanitize_html(&option.text) }
+                            span {
+                                color: palette.muted,
+                                padding_left: "0.5rem",
+                                if option.score == 1 { "1 vote" } else { "{option.score} votes" }
+                            }
+                        }
                     }
 
-                    for comment in &story.comments {
-                        Comment {
-                            comment: comment.clone()
+                    if !story.comments.is_empty() {
+                        div {
+                            display: "flex",
+                            align_items: "center",
+                            padding_bottom: "0.5rem",
+                            input {
+                                flex: "1",
+                                r#type: "search",
+                                placeholder: "Search comments",
+                                value: "{comment_query().0}",
+                                oninput: move |event| comment_query.set(CommentQuery(event.value())),
+                            }
+                            if !query.is_empty() {
+                                span {
+                                    color: palette.muted,
+                                    padding_left: "0.5rem",
+                                    if matches == 1 { "1 match" } else { "{matches} matches" }
+                                }
+                            }
+                        }
+                    }
+                    // Keyed by story so a new story starts scrolled to the top.
+                    ScrollPane {
+                        key: "{story_id}",
+                        id: "preview-comments",
+                        if story.comments.is_empty() {
+                            div { padding: "0.5rem", color: palette.muted, "No comments yet" }
+                        }
+                        // Keyed by id so collapsed state resets when another story loads.
+                        // HN ranks the best comment first, so it gets set apart.
+                        for (index, comment) in story.comments.iter().enumerate().filter(|(_, comment)| comment.thread_matches(&query)).take(comment_limit()) {
+                            div {
+                                key: "{comment.id}",
+                                background: if index == 0 { palette.highlight } else { "transparent" },
+                                border_left: if index == 0 { top_border.as_str() } else { "none" },
+                                ErrorBoundary {
+                                    handle_error: fallback("Couldn't render this comment"),
+                                    Comment { comment: comment.clone(), depth: 0 }
+                                }
+                            }
+                        }
+                        if remaining_comments > 0 {
+                            button {
+                                margin: "0.5rem",
+                                onclick: move |_event| comment_limit += COMMENT_PAGE_SIZE,
+                                "show more comments ({remaining_comments} left)"
+                            }
                         }
                     }
                 }
@@ -106,24 +1410,276 @@ fn Preview() -> Element {
     }
 }
 
+/// The image and description a link story's page advertises, if it has any
+/// and lets us fetch it; otherwise nothing at all.
 #[component]
-fn Comment(comment: Comment) -> Element {
+fn LinkCard(url: String) -> Element {
+    let palette = use_palette();
+    let provider = consume_context::<Provider>();
+    let preview = use_resource(use_reactive((&url,), move |(url,)| async move {
+        let source = provider.read().clone();
+        source.fetch_link_preview(&url).await
+    }));
+
+    let preview = match &*preview.read() {
+        Some(Ok(preview)) if !preview.is_empty() => preview.clone(),
+        _ => return rsx! {},
+    };
     rsx! {
         div {
-            padding: "0.5rem",
+            display: "flex",
+            gap: "0.5rem",
+            padding_bottom: "0.5rem",
+            if let Some(image) = preview.image {
+                img {
+                    src: image,
+                    alt: "",
+                    width: "120",
+                    max_height: "80px",
+                    object_fit: "cover",
+                    flex_shrink: "0",
+                }
+            }
+            div {
+                color: palette.muted,
+                font_size: "0.9rem",
+                if let Some(title) = preview.title {
+                    div { font_weight: "bold", "{title}" }
+                }
+                if let Some(description) = preview.description {
+                    "{description}"
+                }
+            }
+        }
+    }
+}
+
+/// A pane filling the rest of its column that scrolls on its own, with a
+/// floating "back to top" button once it has been scrolled far enough.
+#[component]
+fn ScrollPane(id: String, children: Element) -> Element {
+    let palette = use_palette();
+    let mut scrolled = use_signal(|| false);
+    let pane_id = id.clone();
+    let top_id = id.clone();
+
+    rsx! {
+        div {
+            flex: "1",
+            min_height: "0",
+            position: "relative",
+            display: "flex",
+            flex_direction: "column",
             div {
-                color: "gray",
-                "by {comment.by}"
+                id: "{id}",
+                flex: "1",
+                min_height: "0",
+                overflow_y: "auto",
+                onscroll: move |_event| {
+                    let pane_id = pane_id.clone();
+                    spawn(async move {
+                        let top = browser::scroll_top(&pane_id).await;
+                        scrolled.set(top.is_some_and(|top| top > BACK_TO_TOP_THRESHOLD));
+                    });
+                },
+                {children}
+            }
+            if scrolled() {
+                button {
+                    position: "absolute",
+                    right: "1rem",
+                    bottom: "1rem",
+                    padding: "0.25rem 0.75rem",
+                    border: "1px solid {palette.border}",
+                    border_radius: "1rem",
+                    background: palette.background,
+                    color: palette.text,
+                    cursor: "pointer",
+                    onclick: move |_event| browser::scroll_to_top(&top_id),
+                    "↑ back to top"
+                }
             }
+        }
+    }
+}
+
+#[component]
+fn Comment(comment: Comment, depth: usize) -> Element {
+    let palette = use_palette();
+    let settings = consume_context::<Signal<Settings>>();
+    let provider = consume_context::<Provider>();
+    let max_depth = settings.read().max_comment_depth;
+    // Read once on mount, so changing the setting doesn't toggle comments already shown.
+    let mut collapsed = use_signal(|| {
+        depth > 0 && settings.peek().default_comment_state == CommentDefault::CollapsedChildren
+    });
+    // Only commands issued after mounting apply, so replies revealed later keep their default.
+    let collapse_all = consume_context::<Signal<CollapseAll>>();
+    let mounted_at = use_hook(|| collapse_all.peek().generation);
+    use_effect(move || {
+        let command = collapse_all();
+        if command.generation != mounted_at {
+            collapsed.set(command.collapsed);
+        }
+    });
+    let mut preview_state = consume_context::<Signal<PreviewState>>();
+    let mut loading_replies = use_signal(|| false);
+    let mut expanded = use_signal(|| false);
+    // Measured on the plain text, so markup doesn't count and a cut never lands in a tag.
+    let long = util::strip_html(&comment.text).chars().count() > LONG_COMMENT_LENGTH;
+    let replies = comment.sub_comments.clone();
+    let unloaded = replies.is_empty() && !comment.kids.is_empty();
+    let kids = comment.kids.clone();
+    let comment_id = comment.id;
+    // Until replies are loaded, only the direct ones are known.
+    let reply_count = if unloaded {
+        comment.kids.len()
+    } else {
+        comment.descendant_count()
+    };
+    // While searching, threads without a match are hidden and comments that are
+    // only kept as context for a matching reply are dimmed.
+    let CommentQuery(query) = consume_context::<Signal<CommentQuery>>()();
+    let own_match = comment.text_matches(&query);
+    if !comment.thread_matches(&query) {
+        return rsx! {};
+    }
+    let spacing = settings.read().density.spacing();
+    // Replies get a bar in their level's color; top-level comments sit flush.
+    let depth_bar = match depth {
+        0 => "none".to_string(),
+        depth => format!(
+            "2px solid {}",
+            palette.depth[(depth - 1) % palette.depth.len()]
+        ),
+    };
 
+    rsx! {
+        div {
+            id: "comment-{comment.id}",
+            padding: spacing.padding,
+            margin_left: if depth > 0 { "0.5rem" } else { "0" },
+            border_left: "{depth_bar}",
+            role: "article",
+            aria_label: "Comment by {comment.by}",
             div {
-                dangerous_inner_html:
-                "{comment.text}"
+                color: palette.muted,
+                a {
+                    href: "#",
+                    color: palette.muted,
+                    text_decoration: "none",
+                    role: "button",
+                    aria_expanded: "{!collapsed()}",
+                    aria_label: if collapsed() { "Expand comment" } else { "Collapse comment" },
+                    prevent_default: "onclick",
+                    onclick: move |_event| collapsed.toggle(),
+                    if collapsed() { "[+]" } else { "[-]" }
+                }
+                if comment.placeholder().is_none() {
+                    " by "
+                    UserLink { username: comment.by.clone() }
+                }
+                " · "
+                a {
+                    href: util::hn_item_url(comment.id),
+                    color: palette.muted,
+                    Timestamp { time: comment.time }
+                }
+                // Top-level comments reply to the story itself, which is already in view.
+                if let Some(parent) = comment.parent.filter(|_| depth > 0) {
+                    " · "
+                    a {
+                        href: util::hn_item_url(parent),
+                        color: palette.muted,
+                        prevent_default: "onclick",
+                        onclick: move |_event| {
+                            spawn(async move {
+                                // Parents outside the loaded tree open on HN instead.
+                                if !browser::reveal(&format!("comment-{parent}")).await {
+                                    browser::open(&util::hn_item_url(parent));
+                                }
+                            });
+                        },
+                        "parent"
+                    }
+                }
+                if collapsed() {
+                    if reply_count == 1 { " · 1 reply" } else { " · {reply_count} replies" }
+                }
             }
 
-            for kid in &comment.sub_comments {
-                Comment {
-                    comment: kid.clone()
+            if !collapsed() {
+                if let Some(placeholder) = comment.placeholder() {
+                    div { color: palette.muted, font_style: "italic", "{placeholder}" }
+                } else {
+                    if long && !expanded() {
+                        div {
+                            opacity: if own_match { "1" } else { "0.5" },
+                            "{util::excerpt(&comment.text, LONG_COMMENT_LENGTH)}"
+                        }
+                    } else {
+                        div {
+                            opacity: if own_match { "1" } else { "0.5" },
+                            dangerous_inner_html: util::sanitize_html(&comment.text)
+                        }
+                    }
+                    if long {
+                        a {
+                            href: "#",
+                            color: palette.muted,
+                            font_size: "0.9rem",
+                            prevent_default: "onclick",
+                            onclick: move |_event| expanded.toggle(),
+                            if expanded() { "show less" } else { "read more" }
+                        }
+                    }
+                }
+
+                if depth + 1 < max_depth {
+                    for kid in replies {
+                        ErrorBoundary {
+                            key: "{kid.id}",
+                            handle_error: fallback("Couldn't render this comment"),
+                            Comment { comment: kid, depth: depth + 1 }
+                        }
+                    }
+                    if unloaded {
+                        a {
+                            href: "#",
+                            color: palette.muted,
+                            prevent_default: "onclick",
+                            onclick: move |_event| {
+                                if loading_replies() {
+                                    return;
+                                }
+                                let kids = kids.clone();
+                                let source = provider.read().clone();
+                                loading_replies.set(true);
+                                spawn(async move {
+                                    let fetched = source.get_comments(&kids, 0).await;
+                                    // Into the previewed story, so searching the thread finds them too.
+                                    if let PreviewState::Loaded(story) = &mut *preview_state.write() {
+                                        if let Some(comment) = story.comment_mut(comment_id) {
+                                            comment.sub_comments = fetched;
+                                        }
+                                    }
+                                    loading_replies.set(false);
+                                });
+                            },
+                            if loading_replies() {
+                                "loading replies..."
+                            } else if comment.kids.len() == 1 {
+                                "show 1 reply"
+                            } else {
+                                "show {comment.kids.len()} replies"
+                            }
+                        }
+                    }
+                } else if !comment.kids.is_empty() {
+                    a {
+                        href: util::hn_item_url(comment.id),
+                        "Continue this thread"
+                    }
                 }
             }
         }
@@ -133,79 +1689,557 @@ fn Comment(comment: Comment) -> Element {
 // ----------------------------------------------------------------
 
 #[component]
-fn StoryListing(story: ReadOnlySignal<StoryItem>) -> Element {
-    let preview_state = consume_context::<Signal<PreviewState>>();
+fn StoryListing(story: ReadOnlySignal<StoryItem>, rank: usize, selected: bool) -> Element {
+    let palette = use_palette();
+    let mut settings = consume_context::<Signal<Settings>>();
 
+    let title = story.read().display_title();
     let StoryItem {
-        title,
         url,
         by,
         score,
         time,
-        kids,
+        descendants,
         id,
+        text,
         ..
     } = story();
 
-    let full_story = use_signal(|| None);
+    let cache = consume_context::<Signal<StoryCache>>();
+    // Through a memo, so filling the cache only re-renders the listings it changed.
+    let cached_descendants = use_memo(move || {
+        let id = story.read().id;
+        cache.read().get(id).map(|page| page.item.descendants)
+    });
+    let favorite = settings.read().is_favorite(id);
+    let read_later = settings.read().is_read_later(id);
+    let history = consume_context::<Signal<ScoreHistory>>();
+    let trend = |samples: &[(DateTime<Utc>, i64)]| {
+        let values: Vec<i64> = samples.iter().map(|&(_, value)| value).collect();
+        render::sparkline_points(&values, 40.0, 12.0)
+    };
+    let score_trend = trend(history.read().scores(id));
+    let comment_trend = trend(history.read().comments(id));
+    let visited = settings.read().is_visited(id);
+    let spacing = settings.read().density.spacing();
+    let mut hover_task = use_signal(|| None::<Task>);
+    let previews = use_coroutine_handle::<PreviewRequest>();
+    let mut preview_open = consume_context::<Signal<PreviewOpen>>();
 
-    let url = url.as_deref().unwrap_or_default();
+    let url = match url {
+        Some(url) if settings.read().strip_tracking_params => util::clean_url(&url),
+        url => url.unwrap_or_default(),
+    };
+    let url = url.as_str();
+    let discussion_url = util::hn_item_url(id);
+    // Self-posts have no external link, so the title opens the discussion instead.
+    let title_url = if url.is_empty() {
+        discussion_url.clone()
+    } else {
+        url.to_string()
+    };
+    // External links open in a new tab so the reader keeps their place here.
+    let (title_target, title_rel) = if url.is_empty() {
+        ("_self", "")
+    } else {
+        ("_blank", "noopener noreferrer")
+    };
 
-    let mut hostname = url
-        .trim_start_matches("https://")
-        .trim_start_matches("http://")
-        .trim_start_matches("www.");
+    // Self-posts such as Ask HN carry their question in `text`.
+    let excerpt = match &text {
+        Some(text) if url.is_empty() => Some(util::excerpt(text, EXCERPT_LENGTH)),
+        _ => None,
+    };
 
-    if hostname.is_empty() {
-        hostname = "google.com"
-    }
+    let hostname = util::host_of(url).unwrap_or_default();
+    let favicon = util::favicon_url(&hostname);
+    let mute_host = hostname.clone();
+    let mut favicon_failed = use_signal(|| false);
+    // What was last copied, and the text to copy by hand if the clipboard refused.
+    let mut copied = use_signal(|| None::<&'static str>);
+    let mut copy_fallback = use_signal(|| None::<String>);
+    let copy_input_id = format!("copy-{id}");
+    // Sharing goes through the share sheet where there is one, else the clipboard.
+    let copy = move |label: &'static str, text: String| {
+        spawn(async move {
+            let shared = label == "share" && browser::share(&text).await;
+            if shared || browser::copy_to_clipboard(&text).await {
+                copy_fallback.set(None);
+                copied.set(Some(label));
+                tokio::time::sleep(COPIED_DURATION).await;
+                if copied() == Some(label) {
+                    copied.set(None);
+                }
+            } else {
+                copy_fallback.set(Some(text));
+            }
+        });
+    };
+    let copy_url = url.to_string();
+    let copy_discussion_url = discussion_url.clone();
+    let share_text = story.read().share_text();
 
-    let score = format!("{score} {}", if score == 1 { " point" } else { " points" });
+    let score = util::points_label(score);
 
-    let comments = format!(
-        "{} {}",
-        kids.len(),
-        if kids.len() == 1 {
-            " comment"
-        } else {
-            " comments"
-        }
-    );
+    // A thread resolved for the preview is fresher than the list item.
+    let descendants = cached_descendants().unwrap_or(descendants);
 
-    let local_time = time.with_timezone(&Local);
-    let time = local_time.format("%D %l:%M %p");
+    // `kids` only holds direct replies; `descendants` is the thread's total.
+    let comments = (descendants > 0).then(|| util::comments_label(descendants));
 
     rsx! {
         div {
-            padding: "0.5rem",
+            id: "story-{id}",
+            role: "listitem",
+            aria_current: if selected { "true" } else { "false" },
+            padding: spacing.padding,
             position: "relative",
+            display: if spacing.inline_meta { "flex" } else { "block" },
+            flex_wrap: "wrap",
+            align_items: "baseline",
+            background: if selected { palette.highlight } else { "transparent" },
+            // Only preview once the pointer lingers, so scanning the list doesn't fetch every story.
             onmouseenter: move |_event| {
-                api::resolve_story(full_story, preview_state, id)
+                let task = spawn(async move {
+                    tokio::time::sleep(HOVER_DELAY).await;
+                    // Leaving only cancels the delay; the fetch itself belongs to `Stories`.
+                    hover_task.set(None);
+                    previews.send(PreviewRequest(id));
+                });
+                if let Some(previous) = hover_task.replace(Some(task)) {
+                    previous.cancel();
+                }
+            },
+            onmouseleave: move |_event| {
+                if let Some(task) = hover_task.take() {
+                    task.cancel();
+                }
+            },
+            // Touch screens have no hover; tapping a story opens it instead. The
+            // row's own links and buttons stop their clicks short of this.
+            onclick: move |_event| {
+                preview_open.set(PreviewOpen(true));
+                previews.send(PreviewRequest(id));
             },
             div {
-                font_size: "1.5rem",
+                font_size: spacing.title_font_size,
+                span {
+                    color: palette.muted,
+                    padding_right: "0.5rem",
+                    "{rank}."
+                }
                 a {
-                    href: url,
-                    onfocus: move |_event| {
-                        api::resolve_story(full_story, preview_state, id)
+                    href: title_url,
+                    target: title_target,
+                    rel: title_rel,
+                    color: if visited { palette.muted } else { palette.link },
+                    onclick: move |event| {
+                        event.stop_propagation();
+                        settings.write().mark_visited(id);
+                    },
+                    onfocus: move |_event| previews.send(PreviewRequest(id)),
+                    // The link already opens itself; don't let the list open the selection too.
+                    onkeydown: move |event: KeyboardEvent| {
+                        if event.key() == Key::Enter {
+                            event.stop_propagation();
+                        }
                     },
                     "{title}"
                 }
-                a {
-                    color: "gray",
-                    href: "https://news.ycombinator.com/from?site={hostname}",
-                    text_decoration: "none",
-                    " ({hostname})"
+                // Fixed size, so a missing icon doesn't shift the hostname around.
+                span {
+                    display: "inline-block",
+                    width: "16px",
+                    height: "16px",
+                    padding_left: "0.5rem",
+                    vertical_align: "middle",
+                    if !favicon_failed() {
+                        img {
+                            src: favicon,
+                            width: "16",
+                            height: "16",
+                            alt: "",
+                            onerror: move |_event| favicon_failed.set(true),
+                        }
+                    }
+                }
+                if !hostname.is_empty() {
+                    a {
+                        color: palette.muted,
+                        href: "https://news.ycombinator.com/from?site={hostname}",
+                        target: "_blank",
+                        rel: "noopener noreferrer",
+                        text_decoration: "none",
+                        onclick: move |event| event.stop_propagation(),
+                        " ({hostname})"
+                    }
+                    a {
+                        href: "#",
+                        padding_left: "0.25rem",
+                        font_size: "0.8rem",
+                        color: palette.muted,
+                        text_decoration: "none",
+                        title: "Mute {hostname}",
+                        prevent_default: "onclick",
+                        onclick: move |event| {
+                            event.stop_propagation();
+                            settings.write().muted_domains.insert(mute_host.clone());
+                        },
+                        "mute"
+                    }
+                }
+            }
+            if let Some(excerpt) = excerpt.filter(|excerpt| !excerpt.is_empty()) {
+                div {
+                    color: palette.muted,
+                    font_size: "0.9rem",
+                    padding: "0.25rem 0",
+                    "{excerpt}"
                 }
             }
             div {
                 display: "flex",
                 flex_direction: "row",
-                color: "gray",
+                padding_left: if spacing.inline_meta { "0.5rem" } else { "0" },
+                color: palette.muted,
                 div { "{score}" }
-                div { padding_left: "0.5rem", "by {by}" }
-                div { padding_left: "0.5rem", "{time}" }
-                div { padding_left: "0.5rem", "{comments}" }
+                if let Some(points) = score_trend {
+                    Sparkline { points, color: palette.accent }
+                }
+                div {
+                    padding_left: "0.5rem",
+                    onclick: move |event| event.stop_propagation(),
+                    "by "
+                    UserLink { username: by }
+                }
+                div { padding_left: "0.5rem", Timestamp { time } }
+                if let Some(comments) = comments {
+                    div { padding_left: "0.5rem", "{comments}" }
+                }
+                if let Some(points) = comment_trend {
+                    Sparkline { points, color: palette.link }
+                }
+                // The actions act on the story rather than opening it.
+                span {
+                    display: "contents",
+                    onclick: move |event| event.stop_propagation(),
+                    a {
+                        href: discussion_url,
+                        padding_left: "0.5rem",
+                        color: palette.muted,
+                        onclick: move |_event| settings.write().mark_visited(id),
+                        "discuss"
+                    }
+                    a {
+                        href: "#",
+                        padding_left: "0.5rem",
+                        color: if favorite { palette.accent } else { palette.muted },
+                        text_decoration: "none",
+                        title: if favorite { "Unstar" } else { "Star" },
+                        role: "button",
+                        aria_pressed: "{favorite}",
+                        aria_label: if favorite { "Unstar" } else { "Star" },
+                        prevent_default: "onclick",
+                        onclick: move |_event| settings.write().toggle_favorite(id),
+                        if favorite { "★" } else { "☆" }
+                    }
+                    a {
+                        href: "#",
+                        padding_left: "0.5rem",
+                        color: palette.muted,
+                        prevent_default: "onclick",
+                        onclick: move |_event| settings.write().hide(id),
+                        "hide"
+                    }
+                    a {
+                        href: "#",
+                        padding_left: "0.5rem",
+                        color: palette.muted,
+                        prevent_default: "onclick",
+                        onclick: move |_event| settings.write().toggle_read_later(id),
+                        if read_later { "remove from read later" } else { "read later" }
+                    }
+                    if !url.is_empty() {
+                        a {
+                            href: "#",
+                            padding_left: "0.5rem",
+                            color: palette.muted,
+                            title: "Copy the link",
+                            prevent_default: "onclick",
+                            onclick: move |_event| copy("link", copy_url.clone()),
+                            if copied() == Some("link") { "copied!" } else { "copy link" }
+                        }
+                    }
+                    a {
+                        href: "#",
+                        padding_left: "0.5rem",
+                        color: palette.muted,
+                        title: "Copy the HN discussion link",
+                        prevent_default: "onclick",
+                        onclick: move |_event| copy("discussion", copy_discussion_url.clone()),
+                        if copied() == Some("discussion") { "copied!" } else { "copy hn link" }
+                    }
+                    a {
+                        href: "#",
+                        padding_left: "0.5rem",
+                        color: palette.muted,
+                        title: "Share the HN discussion",
+                        prevent_default: "onclick",
+                        onclick: move |_event| copy("share", share_text.clone()),
+                        if copied() == Some("share") { "shared!" } else { "share" }
+                    }
+                }
+            }
+            // Without clipboard access, select the text so it can be copied by hand.
+            if let Some(text) = copy_fallback() {
+                input {
+                    id: "{copy_input_id}",
+                    readonly: true,
+                    value: "{text}",
+                    width: "100%",
+                    onclick: move |event| event.stop_propagation(),
+                    onmounted: move |_event| browser::select_text(&copy_input_id),
+                    onblur: move |_event| copy_fallback.set(None),
+                }
+            }
+        }
+    }
+}
+
+/// A 40×12 trend line through `points`, as laid out by [`render::sparkline_points`].
+#[component]
+fn Sparkline(points: String, color: &'static str) -> Element {
+    rsx! {
+        svg {
+            width: "40",
+            height: "12",
+            view_box: "0 0 40 12",
+            style: "margin-left: 0.25rem",
+            polyline {
+                points: "{points}",
+                fill: "none",
+                stroke: color,
+                stroke_width: "1.5",
+            }
+        }
+    }
+}
+
+/// A job posting: its title linking to the posting, the company's host and
+/// when it was posted, without the score, author and comments of a story.
+#[component]
+fn JobListing(story: ReadOnlySignal<StoryItem>, rank: usize, selected: bool) -> Element {
+    let palette = use_palette();
+    let mut settings = consume_context::<Signal<Settings>>();
+
+    let title = story.read().display_title();
+    let StoryItem { url, time, id, .. } = story();
+    let spacing = settings.read().density.spacing();
+    let visited = settings.read().is_visited(id);
+
+    // Some postings only live on HN.
+    let url = url.unwrap_or_else(|| util::hn_item_url(id));
+    let hostname = util::host_of(&url).unwrap_or_default();
+    rsx! {
+        div {
+            id: "story-{id}",
+            role: "listitem",
+            aria_current: if selected { "true" } else { "false" },
+            padding: spacing.padding,
+            background: if selected { palette.highlight } else { "transparent" },
+            div {
+                font_size: spacing.title_font_size,
+                span {
+                    color: palette.muted,
+                    padding_right: "0.5rem",
+                    "{rank}."
+                }
+                a {
+                    href: "{url}",
+                    target: "_blank",
+                    rel: "noopener noreferrer",
+                    color: if visited { palette.muted } else { palette.link },
+                    onclick: move |_event| settings.write().mark_visited(id),
+                    "{title}"
+                }
+                if !hostname.is_empty() {
+                    span { color: palette.muted, " ({hostname})" }
+                }
+            }
+            div {
+                color: palette.muted,
+                Timestamp { time }
+                a {
+                    href: "#",
+                    padding_left: "0.5rem",
+                    color: palette.muted,
+                    prevent_default: "onclick",
+                    onclick: move |_event| settings.write().hide(id),
+                    "hide"
+                }
+            }
+        }
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// When something was posted, in the `TimeFormat` of the settings, with the
+/// exact local and UTC times as a tooltip.
+#[component]
+fn Timestamp(#[props(into)] time: Option<DateTime<Utc>>) -> Element {
+    let settings = consume_context::<Signal<Settings>>();
+    let Some(time) = time else {
+        return rsx! { "unknown time" };
+    };
+    let text = settings.read().time_format.format(time);
+
+    rsx! {
+        span { title: util::time_tooltip(time), "{text}" }
+    }
+}
+
+/// An author's name, opening their profile panel when clicked.
+#[component]
+fn UserLink(username: String) -> Element {
+    let palette = use_palette();
+    let mut open_profile = consume_context::<Signal<OpenProfile>>();
+    let name = username.clone();
+
+    rsx! {
+        a {
+            href: "#",
+            color: palette.muted,
+            prevent_default: "onclick",
+            onclick: move |_event| open_profile.set(OpenProfile(Some(name.clone()))),
+            "{username}"
+        }
+    }
+}
+
+/// A small floating panel with the karma and "about" text of the open profile.
+fn ProfilePanel() -> Element {
+    let palette = use_palette();
+    let mut open_profile = consume_context::<Signal<OpenProfile>>();
+    let provider = consume_context::<Provider>();
+
+    let user = use_resource(move || {
+        let OpenProfile(username) = open_profile();
+        let source = provider.read().clone();
+        async move {
+            match username {
+                Some(username) => Some(source.get_user(&username).await),
+                None => None,
+            }
+        }
+    });
+
+    let OpenProfile(Some(username)) = open_profile() else {
+        return None;
+    };
+
+    let body = match &*user.read_unchecked() {
+        Some(Some(Ok(User {
+            id,
+            created,
+            karma,
+            about,
+        }))) => {
+            let joined = util::humanize_time(*created);
+            rsx! {
+                div { font_weight: "bold", "{id}" }
+                div { color: palette.muted, "{karma} karma · joined {joined}" }
+                match about.as_deref().filter(|about| !about.trim().is_empty()) {
+                    Some(about) => rsx! {
+                        div {
+                            padding_top: "0.5rem",
+                            dangerous_inner_html: util::sanitize_html(about)
+                        }
+                    },
+                    None => rsx! {
+                        div { padding_top: "0.5rem", color: palette.muted, font_style: "italic", "No about section." }
+                    },
+                }
+            }
+        }
+        Some(Some(Err(err))) => rsx! { "Couldn't load {username}: {err}" },
+        _ => rsx! { Spinner { label: "Loading {username}..." } },
+    };
+
+    rsx! {
+        div {
+            position: "fixed",
+            right: "1rem",
+            bottom: "1rem",
+            width: "20rem",
+            max_height: "50vh",
+            overflow_y: "auto",
+            padding: "0.5rem",
+            background: palette.background,
+            border: "1px solid {palette.border}",
+            border_radius: "4px",
+            a {
+                href: "#",
+                float: "right",
+                color: palette.muted,
+                text_decoration: "none",
+                title: "Close",
+                prevent_default: "onclick",
+                onclick: move |_event| open_profile.set(OpenProfile::default()),
+                "×"
+            }
+            {body}
+        }
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// A "×" in the preview's top corner that empties it, like pressing Escape.
+#[component]
+fn ClosePreview() -> Element {
+    let palette = use_palette();
+    let preview_state = consume_context::<Signal<PreviewState>>();
+    let open = consume_context::<Signal<PreviewOpen>>();
+
+    rsx! {
+        button {
+            float: "right",
+            margin: "0.25rem",
+            border: "none",
+            background: "transparent",
+            color: palette.muted,
+            font_size: "1.25rem",
+            cursor: "pointer",
+            title: "Close the preview (Esc)",
+            onclick: move |_event| close_preview(preview_state, open),
+            "×"
+        }
+    }
+}
+
+/// A spinning indicator for pending async resources, with an optional label.
+#[component]
+fn Spinner(#[props(into)] label: Option<String>) -> Element {
+    let palette = use_palette();
+    let ring = format!("2px solid {}", palette.border);
+    rsx! {
+        style { "@keyframes hn-spinner {{ to {{ transform: rotate(360deg); }} }}" }
+        div {
+            display: "flex",
+            align_items: "center",
+            padding: "0.5rem",
+            color: palette.muted,
+            div {
+                width: "1rem",
+                height: "1rem",
+                border: ring,
+                border_top_color: palette.muted,
+                border_radius: "50%",
+                animation: "hn-spinner 0.8s linear infinite",
+            }
+            if let Some(label) = label {
+                span { padding_left: "0.5rem", "{label}" }
             }
         }
     }
@@ -0,0 +1,280 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+use chrono::{DateTime, Utc};
+
+use crate::types::{Comment, StoryItem, StoryPageData};
+use crate::util;
+
+// ----------------------------------------------------------------
+
+// Render resolved Hackernews data into export formats.
+
+/// Renders a story and its comment tree as Markdown, with times relative to now.
+pub fn to_markdown(page: &StoryPageData) -> String {
+    to_markdown_at(page, Utc::now())
+}
+
+/// Renders a story and its comment tree as Markdown, with times relative to `now`.
+/// Comments become nested blockquotes headed by their author and age.
+pub fn to_markdown_at(page: &StoryPageData, now: DateTime<Utc>) -> String {
+    let item = &page.item;
+    let mut markdown = match &item.url {
+        Some(url) => format!("# [{}]({})\n\n", item.display_title(), url),
+        None => format!("# {}\n\n", item.display_title()),
+    };
+
+    markdown.push_str(&format!(
+        "{} by {} · {}\n\n",
+        util::points_label(item.score),
+        item.by,
+        item.time.map_or_else(
+            || "unknown time".to_string(),
+            |time| util::humanize_time_at(time, now)
+        )
+    ));
+
+    if let Some(text) = &item.text {
+        markdown.push_str(&util::strip_html(text));
+        markdown.push_str("\n\n");
+    }
+
+    for comment in &page.comments {
+        push_comment_markdown(&mut markdown, comment, 1, now);
+    }
+
+    markdown.trim_end().to_string() + "\n"
+}
+
+fn push_comment_markdown(
+    markdown: &mut String,
+    comment: &Comment,
+    depth: usize,
+    now: DateTime<Utc>,
+) {
+    let prefix = "> ".repeat(depth);
+    let quote_break = prefix.trim_end();
+
+    let text = match comment.placeholder() {
+        Some(placeholder) => placeholder.to_string(),
+        None => {
+            markdown.push_str(&format!(
+                "{prefix}**{}** · {}\n{quote_break}\n",
+                comment.by,
+                util::humanize_time_at(comment.time, now)
+            ));
+            util::strip_html(&comment.text)
+        }
+    };
+    for line in text.lines() {
+        if line.is_empty() {
+            markdown.push_str(quote_break);
+        } else {
+            markdown.push_str(&prefix);
+            markdown.push_str(line);
+        }
+        markdown.push('\n');
+    }
+    markdown.push_str(quote_break);
+    markdown.push('\n');
+
+    for reply in &comment.sub_comments {
+        push_comment_markdown(markdown, reply, depth + 1, now);
+    }
+
+    // Close this comment's quote so the next sibling starts its own.
+    markdown.push_str("> ".repeat(depth - 1).trim_end());
+    markdown.push('\n');
+}
+
+/// Renders a story and its comment tree as plain text, with times relative to now.
+pub fn thread_to_text(page: &StoryPageData) -> String {
+    thread_to_text_at(page, Utc::now())
+}
+
+/// Renders a story and its comment tree as plain text for a terminal, with times
+/// relative to `now`. Each comment is headed by its author and age and indented
+/// two spaces per level of nesting.
+pub fn thread_to_text_at(page: &StoryPageData, now: DateTime<Utc>) -> String {
+    let item = &page.item;
+    let mut text = format!("{}\n", item.display_title());
+    if let Some(url) = item.url.as_deref().filter(|url| !url.is_empty()) {
+        text.push_str(&format!("{url}\n"));
+    }
+    text.push_str(&format!(
+        "{} by {} | {} | {}\n\n",
+        util::points_label(item.score),
+        item.by,
+        item.time.map_or_else(
+            || "unknown time".to_string(),
+            |time| util::humanize_time_at(time, now)
+        ),
+        util::comments_label(item.descendants)
+    ));
+
+    if let Some(story_text) = &item.text {
+        text.push_str(&util::strip_html(story_text));
+        text.push_str("\n\n");
+    }
+
+    for comment in &page.comments {
+        push_comment_text(&mut text, comment, 0, now);
+    }
+
+    text.trim_end().to_string() + "\n"
+}
+
+fn push_comment_text(text: &mut String, comment: &Comment, depth: usize, now: DateTime<Utc>) {
+    let indent = "  ".repeat(depth);
+
+    match comment.placeholder() {
+        Some(placeholder) => text.push_str(&format!("{indent}{placeholder}\n")),
+        None => {
+            text.push_str(&format!(
+                "{indent}{} · {}\n",
+                comment.by,
+                util::humanize_time_at(comment.time, now)
+            ));
+            for line in util::strip_html(&comment.text).lines() {
+                if !line.is_empty() {
+                    text.push_str(&indent);
+                    text.push_str(line);
+                }
+                text.push('\n');
+            }
+        }
+    }
+    text.push('\n');
+
+    for reply in &comment.sub_comments {
+        push_comment_text(text, reply, depth + 1, now);
+    }
+}
+
+/// Renders stories as plain text for a terminal, one numbered story per pair
+/// of lines: the title and host, then the score, author and comment count.
+pub fn to_text(items: &[StoryItem]) -> String {
+    let mut text = String::new();
+    for (index, item) in items.iter().enumerate() {
+        let rank = format!("{}.", index + 1);
+        text.push_str(&format!("{rank:>4} {}", item.display_title()));
+        if let Some(host) = item.host() {
+            text.push_str(&format!(" ({host})"));
+        }
+        text.push('\n');
+
+        text.push_str(&format!(
+            "     {} by {} | {}\n",
+            util::points_label(item.score),
+            item.by,
+            util::comments_label(item.descendants),
+        ));
+    }
+    text
+}
+
+/// Renders stories as an RSS 2.0 feed. Each item links to the story's URL, or
+/// to its HN discussion for self-posts, and describes its score and comments.
+pub fn to_rss(items: &[StoryItem]) -> String {
+    let mut rss = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<rss version=\"2.0\">\n",
+        "<channel>\n",
+        "<title>Hacker News</title>\n",
+        "<link>https://news.ycombinator.com/</link>\n",
+        "<description>Stories from Hacker News</description>\n",
+    ));
+
+    for item in items {
+        let discussion_url = util::hn_item_url(item.id);
+        let link = item
+            .url
+            .as_deref()
+            .filter(|url| !url.is_empty())
+            .unwrap_or(&discussion_url);
+
+        rss.push_str("<item>\n");
+        rss.push_str(&format!(
+            "<title>{}</title>\n",
+            escape_xml(&item.display_title())
+        ));
+        rss.push_str(&format!("<link>{}</link>\n", escape_xml(link)));
+        rss.push_str(&format!(
+            "<guid isPermaLink=\"true\">{discussion_url}</guid>\n"
+        ));
+        rss.push_str(&format!("<comments>{discussion_url}</comments>\n"));
+        if let Some(time) = item.time {
+            rss.push_str(&format!("<pubDate>{}</pubDate>\n", time.to_rfc2822()));
+        }
+        rss.push_str(&format!(
+            "<description>{}</description>\n",
+            escape_xml(&format!(
+                "{} by {} · {}",
+                util::points_label(item.score),
+                item.by,
+                util::comments_label(item.descendants)
+            ))
+        ));
+        rss.push_str("</item>\n");
+    }
+
+    rss.push_str("</channel>\n</rss>\n");
+    rss
+}
+
+/// The `points` of an SVG `polyline` drawing `values` left to right across a
+/// `width` by `height` box, the highest value at the top. `None` with fewer
+/// than two values, as there is no line to draw.
+pub fn sparkline_points(values: &[i64], width: f64, height: f64) -> Option<String> {
+    if values.len() < 2 {
+        return None;
+    }
+
+    let min = *values.iter().min()?;
+    let max = *values.iter().max()?;
+    let step = width / (values.len() - 1) as f64;
+    let points = values
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            // A flat series is drawn across the middle.
+            let y = if max == min {
+                height / 2.0
+            } else {
+                height - (value - min) as f64 / (max - min) as f64 * height
+            };
+            format!("{:.1},{:.1}", i as f64 * step, y)
+        })
+        .collect::<Vec<_>>();
+    Some(points.join(" "))
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
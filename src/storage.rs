@@ -0,0 +1,56 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+use dioxus::prelude::eval;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::browser::quote;
+use crate::types::Feed;
+
+// ----------------------------------------------------------------
+
+// Persist values as JSON in the webview's `localStorage`, through `eval` so the
+// same code works on desktop and web.
+
+pub static SETTINGS_KEY: &str = "hackernews.settings";
+pub static STORY_CACHE_KEY: &str = "hackernews.story_cache";
+/// The id of the story open in the preview, so a reload reopens it.
+pub static PREVIEW_KEY: &str = "hackernews.preview";
+
+/// Where the last successfully fetched stories of `feed` are kept for offline use.
+pub fn stories_key(feed: Feed) -> String {
+    format!("hackernews.stories.{}", feed.endpoint())
+}
+
+// ----------------------------------------------------------------
+
+/// Reads `key`, returning `None` if it is missing, unreadable or no longer
+/// matches the shape of `T`.
+pub async fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let script = format!("return localStorage.getItem({});", quote(key));
+    let stored = eval(&script).join().await.ok()?;
+    serde_json::from_str(stored.as_str()?).ok()
+}
+
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    if let Ok(json) = serde_json::to_string(value) {
+        let script = format!("localStorage.setItem({}, {});", quote(key), quote(&json));
+        let _ = eval(&script);
+    }
+}
@@ -16,8 +16,14 @@
 
 // ----------------------------------------------------------------
 
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::util;
 
 // ----------------------------------------------------------------
 
@@ -28,6 +34,42 @@ pub struct StoryPageData {
     pub item: StoryItem,
     #[serde(default)]
     pub comments: Vec<Comment>,
+    #[serde(default)]
+    pub poll_options: Vec<PollOption>,
+}
+
+impl StoryPageData {
+    /// Every comment in the fetched tree, nested replies included.
+    pub fn fetched_comment_count(&self) -> usize {
+        self.comments
+            .iter()
+            .map(|comment| 1 + comment.descendant_count())
+            .sum()
+    }
+
+    /// How many comments are deleted or unreachable. A fully fetched tree is
+    /// compared against the story's `descendants`; while replies are left to
+    /// load, only the levels that were fetched can be, by the `kids` that
+    /// didn't come back.
+    pub fn hidden_comment_count(&self) -> usize {
+        if !self.comments.iter().any(Comment::has_unloaded_replies) {
+            let descendants = usize::try_from(self.item.descendants).unwrap_or_default();
+            return descendants.saturating_sub(self.fetched_comment_count());
+        }
+        self.item.kids.len().saturating_sub(self.comments.len())
+            + self
+                .comments
+                .iter()
+                .map(Comment::missing_reply_count)
+                .sum::<usize>()
+    }
+
+    /// The comment `id`, wherever it sits in the fetched tree.
+    pub fn comment_mut(&mut self, id: i64) -> Option<&mut Comment> {
+        self.comments
+            .iter_mut()
+            .find_map(|comment| comment.find_mut(id))
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -39,16 +81,116 @@ pub struct Comment {
     pub text: String,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub time: DateTime<Utc>,
+    /// The comment or story this replies to.
+    #[serde(default)]
+    pub parent: Option<i64>,
     #[serde(default)]
     pub kids: Vec<i64>,
     #[serde(default)]
     pub sub_comments: Vec<Comment>,
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub dead: bool,
     pub r#type: String,
 }
 
+impl Comment {
+    pub fn item_type(&self) -> ItemType {
+        ItemType::from(self.r#type.as_str())
+    }
+
+    /// Every reply below this comment, nested ones included, as far as
+    /// `sub_comments` have been fetched.
+    pub fn descendant_count(&self) -> usize {
+        self.sub_comments
+            .iter()
+            .map(|reply| 1 + reply.descendant_count())
+            .sum()
+    }
+
+    /// Case-insensitive substring match on the comment's plain text; an empty
+    /// query matches everything, deleted and flagged comments match nothing else.
+    pub fn text_matches(&self, query: &str) -> bool {
+        let query = query.trim();
+        if query.is_empty() {
+            return true;
+        }
+        self.placeholder().is_none()
+            && util::strip_html(&self.text)
+                .to_lowercase()
+                .contains(&query.to_lowercase())
+    }
+
+    /// Whether this comment or any fetched reply below it matches `query`, i.e.
+    /// whether it stays visible while searching the thread.
+    pub fn thread_matches(&self, query: &str) -> bool {
+        self.text_matches(query)
+            || self
+                .sub_comments
+                .iter()
+                .any(|reply| reply.thread_matches(query))
+    }
+
+    /// How many comments in this thread, this one included, match `query`.
+    pub fn match_count(&self, query: &str) -> usize {
+        usize::from(self.text_matches(query))
+            + self
+                .sub_comments
+                .iter()
+                .map(|reply| reply.match_count(query))
+                .sum::<usize>()
+    }
+
+    /// This comment or the reply `id` below it.
+    pub fn find_mut(&mut self, id: i64) -> Option<&mut Comment> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.sub_comments
+            .iter_mut()
+            .find_map(|reply| reply.find_mut(id))
+    }
+
+    /// Whether some reply below this comment has `kids` that weren't fetched.
+    pub fn has_unloaded_replies(&self) -> bool {
+        (self.sub_comments.is_empty() && !self.kids.is_empty())
+            || self.sub_comments.iter().any(Comment::has_unloaded_replies)
+    }
+
+    /// Replies listed in `kids` that didn't come back, at the levels that were
+    /// fetched; replies left to load on demand don't count.
+    pub fn missing_reply_count(&self) -> usize {
+        if self.sub_comments.is_empty() {
+            return 0;
+        }
+        self.kids.len().saturating_sub(self.sub_comments.len())
+            + self
+                .sub_comments
+                .iter()
+                .map(Comment::missing_reply_count)
+                .sum::<usize>()
+    }
+
+    /// What to show instead of the text of a deleted or flagged comment.
+    pub fn placeholder(&self) -> Option<&'static str> {
+        if self.deleted {
+            Some("[deleted]")
+        } else if self.dead {
+            Some("[flagged]")
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct StoryItem {
     pub id: i64,
+    /// As HN sends it, entity-encoded (e.g. `Don&#x27;t`), so saving and
+    /// restoring a story never decodes it twice; see [`Self::display_title`].
+    /// Empty when HN sends none, as for some polls and deleted items.
+    #[serde(default, deserialize_with = "null_as_empty")]
     pub title: String,
     pub url: Option<String>,
     pub text: Option<String>,
@@ -58,16 +200,699 @@ pub struct StoryItem {
     pub score: i64,
     #[serde(default)]
     pub descendants: i64,
-    #[serde(with = "chrono::serde::ts_seconds")]
-    pub time: DateTime<Utc>,
+    /// Missing on some malformed items; those still list, just without an age.
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    pub time: Option<DateTime<Utc>>,
     #[serde(default)]
     pub kids: Vec<i64>,
+    /// Poll option ids, only set on polls.
+    #[serde(default)]
+    pub parts: Vec<i64>,
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub dead: bool,
+    pub r#type: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PollOption {
+    pub id: i64,
+    #[serde(default)]
+    pub poll: i64,
+    #[serde(default)]
+    pub by: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub score: i64,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub time: DateTime<Utc>,
     pub r#type: String,
 }
 
+/// A Hacker News account, as returned by the `user/` endpoint.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    pub id: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created: DateTime<Utc>,
+    #[serde(default)]
+    pub karma: i64,
+    /// Self-description as HTML; many accounts never set one.
+    #[serde(default)]
+    pub about: Option<String>,
+}
+
+impl StoryItem {
+    pub fn item_type(&self) -> ItemType {
+        ItemType::from(self.r#type.as_str())
+    }
+
+    /// Deleted and dead stories have nothing worth listing.
+    pub fn is_renderable(&self) -> bool {
+        !self.deleted && !self.dead
+    }
+
+    /// The title to show, decoded, with a placeholder for stories that have none.
+    pub fn display_title(&self) -> String {
+        if self.title.trim().is_empty() {
+            "(untitled)".to_string()
+        } else {
+            util::decode_html_entities(&self.title)
+        }
+    }
+
+    /// A message to share the story's HN discussion with, e.g.
+    /// `"Show HN: x — https://news.ycombinator.com/item?id=1"`.
+    pub fn share_text(&self) -> String {
+        format!("{} — {}", self.display_title(), util::hn_item_url(self.id))
+    }
+
+    /// The normalized host of the story's link; `None` for self-posts.
+    pub fn host(&self) -> Option<String> {
+        self.url.as_deref().and_then(util::host_of)
+    }
+
+    /// Case-insensitive substring match on the title; an empty query matches everything.
+    pub fn title_matches(&self, query: &str) -> bool {
+        let query = query.trim();
+        query.is_empty()
+            || self
+                .display_title()
+                .to_lowercase()
+                .contains(&query.to_lowercase())
+    }
+}
+
+/// The HN item `type` field, e.g. `"story"` or `"pollopt"`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ItemType {
+    Story,
+    Job,
+    Comment,
+    Poll,
+    PollOpt,
+    Unknown(String),
+}
+
+impl From<&str> for ItemType {
+    fn from(value: &str) -> Self {
+        match value {
+            "story" => ItemType::Story,
+            "job" => ItemType::Job,
+            "comment" => ItemType::Comment,
+            "poll" => ItemType::Poll,
+            "pollopt" => ItemType::PollOpt,
+            other => ItemType::Unknown(other.to_string()),
+        }
+    }
+}
+
+/// Any HN item, told apart by its `type` field.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    Story(StoryItem),
+    Comment(Comment),
+    Job(StoryItem),
+    Poll(StoryItem),
+    PollOpt(PollOption),
+}
+
+impl Item {
+    pub fn id(&self) -> i64 {
+        match self {
+            Item::Story(story) | Item::Job(story) | Item::Poll(story) => story.id,
+            Item::Comment(comment) => comment.id,
+            Item::PollOpt(option) => option.id,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Item {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let item_type = value
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default();
+        let item = match ItemType::from(item_type) {
+            ItemType::Story => serde_json::from_value(value).map(Item::Story),
+            ItemType::Comment => serde_json::from_value(value).map(Item::Comment),
+            ItemType::Job => serde_json::from_value(value).map(Item::Job),
+            ItemType::Poll => serde_json::from_value(value).map(Item::Poll),
+            ItemType::PollOpt => serde_json::from_value(value).map(Item::PollOpt),
+            ItemType::Unknown(other) => {
+                return Err(D::Error::custom(format!("unknown item type {other:?}")))
+            }
+        };
+        item.map_err(D::Error::custom)
+    }
+}
+
+/// HN sends `null` for some missing titles.
+fn null_as_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Option::<String>::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
 #[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum PreviewState {
     Unset,
     Loading,
     Loaded(StoryPageData),
+    /// Resolving `story_id` failed; `timed_out` tells a slow server from other errors.
+    Failed {
+        story_id: i64,
+        timed_out: bool,
+    },
+}
+
+impl PreviewState {
+    /// The story shown or retried in the preview; `None` while empty or loading.
+    pub fn story_id(&self) -> Option<i64> {
+        match self {
+            PreviewState::Loaded(story) => Some(story.item.id),
+            PreviewState::Failed { story_id, .. } => Some(*story_id),
+            PreviewState::Unset | PreviewState::Loading => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Feed {
+    #[default]
+    Top,
+    New,
+    Best,
+    Ask,
+    Show,
+    Job,
+}
+
+impl Feed {
+    pub const ALL: [Feed; 6] = [
+        Feed::Top,
+        Feed::New,
+        Feed::Best,
+        Feed::Ask,
+        Feed::Show,
+        Feed::Job,
+    ];
+
+    /// The Firebase list endpoint, without the `.json` suffix.
+    pub fn endpoint(&self) -> &'static str {
+        match self {
+            Feed::Top => "topstories",
+            Feed::New => "newstories",
+            Feed::Best => "beststories",
+            Feed::Ask => "askstories",
+            Feed::Show => "showstories",
+            Feed::Job => "jobstories",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Feed::Top => "top",
+            Feed::New => "new",
+            Feed::Best => "best",
+            Feed::Ask => "ask",
+            Feed::Show => "show",
+            Feed::Job => "jobs",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub fn toggled(&self) -> Self {
+        match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::Light,
+        }
+    }
+
+    pub fn palette(&self) -> Palette {
+        match self {
+            Theme::Light => Palette {
+                background: "white",
+                text: "black",
+                muted: "gray",
+                link: "#0000ee",
+                accent: "orange",
+                border: "lightgray",
+                highlight: "#f6f6ef",
+                depth: [
+                    "#ff6600", "#3b82f6", "#10b981", "#a855f7", "#eab308", "#ec4899",
+                ],
+            },
+            Theme::Dark => Palette {
+                background: "#1d1f21",
+                text: "#e0e0e0",
+                muted: "#9a9a9a",
+                link: "#8ab4f8",
+                accent: "orange",
+                border: "#444444",
+                highlight: "#2a2d31",
+                depth: [
+                    "#ff8533", "#60a5fa", "#34d399", "#c084fc", "#facc15", "#f472b6",
+                ],
+            },
+        }
+    }
+}
+
+/// The colors components use, so every theme is defined in one place.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub background: &'static str,
+    pub text: &'static str,
+    pub muted: &'static str,
+    pub link: &'static str,
+    pub accent: &'static str,
+    pub border: &'static str,
+    pub highlight: &'static str,
+    /// The bars beside nested replies, one per level, cycling on deep threads.
+    pub depth: [&'static str; 6],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Score,
+    Time,
+    Comments,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 3] = [SortKey::Score, SortKey::Time, SortKey::Comments];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortKey::Score => "score",
+            SortKey::Time => "time",
+            SortKey::Comments => "comments",
+        }
+    }
+
+    /// Stable sort, so stories with equal keys keep their API order.
+    pub fn sort(&self, stories: &mut [StoryItem], direction: SortDirection) {
+        stories.sort_by(|a, b| self.compare(a, b, direction));
+    }
+
+    pub fn compare(&self, a: &StoryItem, b: &StoryItem, direction: SortDirection) -> Ordering {
+        let ordering = match self {
+            SortKey::Score => a.score.cmp(&b.score),
+            SortKey::Time => a.time.cmp(&b.time),
+            SortKey::Comments => a.descendants.cmp(&b.descendants),
+        };
+        match direction {
+            SortDirection::Ascending => ordering,
+            SortDirection::Descending => ordering.reverse(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    #[default]
+    Descending,
+}
+
+impl SortDirection {
+    pub fn toggled(&self) -> Self {
+        match self {
+            SortDirection::Ascending => SortDirection::Descending,
+            SortDirection::Descending => SortDirection::Ascending,
+        }
+    }
+}
+
+/// How tightly the story list and comments are laid out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Density {
+    #[default]
+    Comfortable,
+    Compact,
+}
+
+impl Density {
+    pub fn toggled(&self) -> Self {
+        match self {
+            Density::Comfortable => Density::Compact,
+            Density::Compact => Density::Comfortable,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Density::Comfortable => "comfortable",
+            Density::Compact => "compact",
+        }
+    }
+
+    pub fn spacing(&self) -> Spacing {
+        match self {
+            Density::Comfortable => Spacing {
+                padding: "0.5rem",
+                title_font_size: "1.5rem",
+                inline_meta: false,
+            },
+            Density::Compact => Spacing {
+                padding: "0.2rem 0.5rem",
+                title_font_size: "1rem",
+                inline_meta: true,
+            },
+        }
+    }
+}
+
+/// Whether timestamps read "3 hours ago" or as the exact local time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeFormat {
+    #[default]
+    Relative,
+    Absolute,
+}
+
+impl TimeFormat {
+    pub fn toggled(&self) -> Self {
+        match self {
+            TimeFormat::Relative => TimeFormat::Absolute,
+            TimeFormat::Absolute => TimeFormat::Relative,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimeFormat::Relative => "relative",
+            TimeFormat::Absolute => "absolute",
+        }
+    }
+
+    pub fn format(&self, time: DateTime<Utc>) -> String {
+        match self {
+            TimeFormat::Relative => util::humanize_time(time),
+            TimeFormat::Absolute => util::local_time(time),
+        }
+    }
+}
+
+/// The inline style values of a `Density`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Spacing {
+    pub padding: &'static str,
+    pub title_font_size: &'static str,
+    /// Whether a story's score, author and links share the title's line.
+    pub inline_meta: bool,
+}
+
+/// How comments start out when a preview opens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommentDefault {
+    #[default]
+    Expanded,
+    /// Only top-level comments are expanded; replies start collapsed.
+    CollapsedChildren,
+}
+
+impl CommentDefault {
+    pub fn toggled(&self) -> Self {
+        match self {
+            CommentDefault::Expanded => CommentDefault::CollapsedChildren,
+            CommentDefault::CollapsedChildren => CommentDefault::Expanded,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CommentDefault::Expanded => "expanded",
+            CommentDefault::CollapsedChildren => "replies collapsed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub story_count: usize,
+    /// Comment levels fetched and rendered before "Continue this thread".
+    pub max_comment_depth: usize,
+    /// Comment levels fetched up front; deeper replies load on demand.
+    pub comment_fetch_depth: usize,
+    pub default_comment_state: CommentDefault,
+    /// Minutes between automatic refreshes of the story list; 0 turns them off.
+    pub refresh_interval: u64,
+    /// Requests to HN allowed in flight at once while loading comments. Higher
+    /// loads big threads faster, lower is gentler on the API.
+    pub max_concurrent_requests: usize,
+    /// Starred story ids, in the order they were starred.
+    pub favorites: Vec<i64>,
+    /// Stories queued to read later, oldest first.
+    pub read_later: Vec<i64>,
+    /// Dismissed story ids, left out of every list.
+    pub hidden: HashSet<i64>,
+    /// Stories whose link or discussion was opened.
+    pub visited: HashSet<i64>,
+    /// Hosts whose stories are left out, subdomains included.
+    pub muted_domains: HashSet<String>,
+    /// Words that hide any story whose title contains them, ignoring case.
+    pub muted_keywords: Vec<String>,
+    /// Stories scoring below this are left out.
+    pub min_score: i64,
+    pub density: Density,
+    pub time_format: TimeFormat,
+    /// Drop `utm_*` and similar tracking parameters from story links.
+    pub strip_tracking_params: bool,
+}
+
+impl Settings {
+    pub const STORY_COUNTS: [usize; 3] = [10, 25, 50];
+    pub const REFRESH_INTERVALS: [u64; 4] = [0, 1, 5, 15];
+
+    pub fn is_favorite(&self, id: i64) -> bool {
+        self.favorites.contains(&id)
+    }
+
+    /// Stars `id` after the stories starred so far, or unstars it.
+    pub fn toggle_favorite(&mut self, id: i64) {
+        match self.favorites.iter().position(|&starred| starred == id) {
+            Some(index) => {
+                self.favorites.remove(index);
+            }
+            None => self.favorites.push(id),
+        }
+    }
+
+    pub fn is_read_later(&self, id: i64) -> bool {
+        self.read_later.contains(&id)
+    }
+
+    /// Queues `id` at the end of the read-later list, or takes it off again.
+    pub fn toggle_read_later(&mut self, id: i64) {
+        match self.read_later.iter().position(|&queued| queued == id) {
+            Some(index) => {
+                self.read_later.remove(index);
+            }
+            None => self.read_later.push(id),
+        }
+    }
+
+    pub fn is_hidden(&self, id: i64) -> bool {
+        self.hidden.contains(&id)
+    }
+
+    pub fn hide(&mut self, id: i64) {
+        self.hidden.insert(id);
+    }
+
+    pub fn is_visited(&self, id: i64) -> bool {
+        self.visited.contains(&id)
+    }
+
+    pub fn mark_visited(&mut self, id: i64) {
+        self.visited.insert(id);
+    }
+
+    /// Whether `host` or one of its parent domains is muted.
+    pub fn is_domain_muted(&self, host: &str) -> bool {
+        self.muted_domains
+            .iter()
+            .any(|domain| host == domain || host.ends_with(&format!(".{domain}")))
+    }
+
+    /// Whether `title` contains one of the muted keywords.
+    pub fn is_title_muted(&self, title: &str) -> bool {
+        let title = title.to_lowercase();
+        self.muted_keywords
+            .iter()
+            .map(|keyword| keyword.trim().to_lowercase())
+            .any(|keyword| !keyword.is_empty() && title.contains(&keyword))
+    }
+
+    /// Mutes `keyword` unless it is blank or already muted.
+    pub fn mute_keyword(&mut self, keyword: &str) {
+        let keyword = keyword.trim();
+        let known = self
+            .muted_keywords
+            .iter()
+            .any(|muted| muted.eq_ignore_ascii_case(keyword));
+        if !keyword.is_empty() && !known {
+            self.muted_keywords.push(keyword.to_string());
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            story_count: 10,
+            max_comment_depth: 8,
+            comment_fetch_depth: 1,
+            default_comment_state: CommentDefault::default(),
+            refresh_interval: 0,
+            max_concurrent_requests: crate::api::MAX_CONCURRENT_REQUESTS,
+            favorites: Vec::new(),
+            read_later: Vec::new(),
+            hidden: HashSet::new(),
+            visited: HashSet::new(),
+            muted_domains: HashSet::new(),
+            muted_keywords: Vec::new(),
+            min_score: 0,
+            density: Density::default(),
+            time_format: TimeFormat::default(),
+            strip_tracking_params: false,
+        }
+    }
+}
+
+/// Resolved stories keyed by id, so hovering a story again doesn't refetch it.
+/// Entries older than `ttl` are treated as missing so comment counts refresh.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoryCache {
+    pub ttl: Duration,
+    entries: HashMap<i64, (DateTime<Utc>, StoryPageData)>,
+}
+
+impl StoryCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, id: i64) -> Option<&StoryPageData> {
+        self.entries
+            .get(&id)
+            .filter(|(fetched_at, _)| !Self::expired(*fetched_at, self.ttl))
+            .map(|(_, story)| story)
+    }
+
+    /// Like `get`, but ignores `ttl`; an old copy beats nothing when offline.
+    pub fn get_stale(&self, id: i64) -> Option<&StoryPageData> {
+        self.entries.get(&id).map(|(_, story)| story)
+    }
+
+    pub fn insert(&mut self, id: i64, story: StoryPageData) {
+        self.entries.insert(id, (Utc::now(), story));
+    }
+
+    pub fn invalidate(&mut self, id: i64) {
+        self.entries.remove(&id);
+    }
+
+    /// Drops every entry older than `ttl`.
+    pub fn invalidate_expired(&mut self) {
+        let ttl = self.ttl;
+        self.entries
+            .retain(|_, (fetched_at, _)| !Self::expired(*fetched_at, ttl));
+    }
+
+    fn expired(fetched_at: DateTime<Utc>, ttl: Duration) -> bool {
+        (Utc::now() - fetched_at)
+            .to_std()
+            .is_ok_and(|age| age > ttl)
+    }
+}
+
+impl Default for StoryCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(5 * 60))
+    }
+}
+
+/// Scores and comment counts sampled over time per story id, kept in memory
+/// only and trimmed to the last [`ScoreHistory::MAX_SAMPLES`] samples each.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScoreHistory {
+    scores: HashMap<i64, Vec<(DateTime<Utc>, i64)>>,
+    comments: HashMap<i64, Vec<(DateTime<Utc>, i64)>>,
+}
+
+impl ScoreHistory {
+    pub const MAX_SAMPLES: usize = 48;
+
+    pub fn record(&mut self, id: i64, at: DateTime<Utc>, score: i64, descendants: i64) {
+        for (series, value) in [(&mut self.scores, score), (&mut self.comments, descendants)] {
+            let series = series.entry(id).or_default();
+            series.push((at, value));
+            if series.len() > Self::MAX_SAMPLES {
+                series.drain(..series.len() - Self::MAX_SAMPLES);
+            }
+        }
+    }
+
+    /// The score samples of `id`, oldest first.
+    pub fn scores(&self, id: i64) -> &[(DateTime<Utc>, i64)] {
+        self.scores.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The comment count samples of `id`, oldest first.
+    pub fn comments(&self, id: i64) -> &[(DateTime<Utc>, i64)] {
+        self.comments.get(&id).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// What a story's link advertises about itself, scraped from the page's
+/// OpenGraph tags. Every field is optional; plenty of pages set none.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LinkPreview {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// An absolute http(s) URL.
+    pub image: Option<String>,
+}
+
+impl LinkPreview {
+    /// Reads the `og:` tags of the page at `page_url`, falling back to its
+    /// `<title>` and meta description. Relative image URLs are resolved
+    /// against `page_url`.
+    pub fn from_html(html: &str, page_url: &str) -> Self {
+        let image = util::meta_content(html, "og:image").and_then(|image| {
+            let image = url::Url::parse(page_url).ok()?.join(&image).ok()?;
+            matches!(image.scheme(), "http" | "https").then(|| image.to_string())
+        });
+
+        Self {
+            title: util::meta_content(html, "og:title").or_else(|| util::html_title(html)),
+            description: util::meta_content(html, "og:description")
+                .or_else(|| util::meta_content(html, "description")),
+            image,
+        }
+    }
+
+    /// Whether there is nothing to show beyond what HN already has.
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none() && self.image.is_none()
+    }
 }
@@ -0,0 +1,391 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Local, Utc};
+use url::Url;
+
+// ----------------------------------------------------------------
+
+const MINUTE: i64 = 60;
+const HOUR: i64 = 60 * MINUTE;
+const DAY: i64 = 24 * HOUR;
+const WEEK: i64 = 7 * DAY;
+
+/// Average adult reading speed, for reading-time estimates.
+const WORDS_PER_MINUTE: usize = 200;
+
+/// The markup HN itself emits in story and comment text.
+const ALLOWED_TAGS: [&str; 5] = ["a", "p", "i", "code", "pre"];
+
+/// Query parameters that only track where a click came from. Anything
+/// starting with `utm_` is treated the same way.
+const TRACKING_PARAMS: [&str; 12] = [
+    "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "igshid", "mc_cid",
+    "mc_eid", "_hsenc", "_hsmi",
+];
+
+// ----------------------------------------------------------------
+
+/// The news.ycombinator.com discussion page for a story or comment.
+pub fn hn_item_url(id: i64) -> String {
+    format!("https://news.ycombinator.com/item?id={id}")
+}
+
+/// The lowercased host of `url` without a leading `www.`, e.g. `"example.com"`
+/// for `"HTTPS://www.Example.com:8080/path"`. Scheme-less URLs are read as http.
+pub fn host_of(url: &str) -> Option<String> {
+    let url = url.trim();
+    if url.is_empty() {
+        return None;
+    }
+
+    let parsed = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            Url::parse(&format!("http://{url}")).ok()?
+        }
+        Err(_) => return None,
+    };
+    let host = parsed.host_str()?;
+    Some(host.strip_prefix("www.").unwrap_or(host).to_string())
+}
+
+/// `url` without its tracking query parameters ([`TRACKING_PARAMS`] and
+/// `utm_*`). Other parameters keep their order and encoding, and the fragment
+/// is kept; URLs that don't parse or carry no trackers come back unchanged.
+pub fn clean_url(url: &str) -> String {
+    let Ok(mut parsed) = Url::parse(url) else {
+        return url.to_string();
+    };
+    let Some(query) = parsed.query() else {
+        return url.to_string();
+    };
+
+    let is_tracking = |pair: &&str| {
+        let key = pair.split('=').next().unwrap_or_default();
+        let key = url::form_urlencoded::parse(key.as_bytes())
+            .next()
+            .map(|(key, _)| key.to_ascii_lowercase())
+            .unwrap_or_default();
+        key.starts_with("utm_") || TRACKING_PARAMS.contains(&key.as_str())
+    };
+    let pairs: Vec<&str> = query.split('&').filter(|pair| !pair.is_empty()).collect();
+    if !pairs.iter().any(is_tracking) {
+        return url.to_string();
+    }
+
+    let kept: Vec<&str> = pairs
+        .into_iter()
+        .filter(|pair| !is_tracking(pair))
+        .collect();
+    let query = kept.join("&");
+    parsed.set_query((!query.is_empty()).then_some(query.as_str()));
+    parsed.to_string()
+}
+
+/// A 16px icon for `host`, or HN's own icon when there is no host (self-posts).
+pub fn favicon_url(host: &str) -> String {
+    if host.is_empty() {
+        "https://news.ycombinator.com/favicon.ico".to_string()
+    } else {
+        format!("https://www.google.com/s2/favicons?domain={host}&sz=16")
+    }
+}
+
+/// Formats `n` with comma thousands separators, e.g. `"1,234,567"`.
+pub fn format_count(n: i64) -> String {
+    let digits = n.unsigned_abs().to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    if n < 0 {
+        formatted.push('-');
+    }
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// A story's score with its unit, e.g. `"1 point"` or `"1,234 points"`.
+pub fn points_label(score: i64) -> String {
+    let unit = if score == 1 { "point" } else { "points" };
+    format!("{} {unit}", format_count(score))
+}
+
+/// A comment count with its unit, e.g. `"1 comment"` or `"1,234 comments"`.
+pub fn comments_label(count: i64) -> String {
+    let unit = if count == 1 { "comment" } else { "comments" };
+    format!("{} {unit}", format_count(count))
+}
+
+/// `time` in the viewer's timezone, e.g. `"10/14/26  3:04 PM"`.
+pub fn local_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local).format("%D %l:%M %p").to_string()
+}
+
+/// The tooltip for a timestamp: the local time, followed by the UTC one.
+pub fn time_tooltip(time: DateTime<Utc>) -> String {
+    format!(
+        "{} ({} UTC)",
+        local_time(time),
+        time.format("%Y-%m-%d %H:%M")
+    )
+}
+
+/// Formats `time` relative to now, e.g. `"3 hours ago"`.
+pub fn humanize_time(time: DateTime<Utc>) -> String {
+    humanize_time_at(time, Utc::now())
+}
+
+/// Formats `time` relative to `now`, e.g. `"3 hours ago"`.
+pub fn humanize_time_at(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - time).num_seconds();
+
+    let (amount, unit) = match seconds {
+        s if s < 1 => return "just now".to_string(),
+        s if s < MINUTE => (s, "second"),
+        s if s < HOUR => (s / MINUTE, "minute"),
+        s if s < DAY => (s / HOUR, "hour"),
+        s if s < WEEK => (s / DAY, "day"),
+        s => (s / WEEK, "week"),
+    };
+
+    format!("{amount} {unit}{} ago", if amount == 1 { "" } else { "s" })
+}
+
+/// Cleans remote HTML down to [`ALLOWED_TAGS`] (plus `href` on links) so it is
+/// safe to hand to `dangerous_inner_html`. Scripts and styles go with their
+/// contents, which would otherwise be left behind as text.
+pub fn sanitize_html(input: &str) -> String {
+    ammonia::Builder::empty()
+        .add_tags(ALLOWED_TAGS)
+        .clean_content_tags(HashSet::from(["script", "style"]))
+        .add_tag_attributes("a", ["href"])
+        .url_schemes(HashSet::from(["http", "https", "mailto"]))
+        .link_rel(Some("noopener noreferrer nofollow"))
+        .clean(input)
+        .to_string()
+}
+
+/// Decodes the HTML entities HN uses in plain-text fields such as titles,
+/// e.g. `&amp;`, `&quot;`, `&#x27;`, `&#x2F;`. Unknown entities are kept as-is.
+pub fn decode_html_entities(input: &str) -> String {
+    let mut decoded = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest
+            .find(';')
+            .and_then(|end| decode_entity(&rest[1..end]).map(|c| (c, end)));
+        match entity {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => {
+            let code = match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => entity.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+/// The number of words in HN's text HTML, once the markup is stripped.
+pub fn word_count(html: &str) -> usize {
+    strip_html(html).split_whitespace().count()
+}
+
+/// Estimated minutes to read HN's text HTML at [`WORDS_PER_MINUTE`], at least one.
+pub fn reading_minutes(html: &str) -> usize {
+    word_count(html).div_ceil(WORDS_PER_MINUTE).max(1)
+}
+
+/// The plain text of HN's text HTML cut to at most `max` characters, at a word
+/// boundary where there is one, with `…` appended when anything was cut.
+/// Entities are decoded first, so a cut never lands inside one.
+pub fn excerpt(html: &str, max: usize) -> String {
+    let text = strip_html(html)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.chars().count() <= max {
+        return text;
+    }
+
+    let end = text.char_indices().nth(max).map_or(text.len(), |(i, _)| i);
+    let cut = &text[..end];
+    // Only back up to a word boundary if the cut fell mid-word.
+    let cut = match (text[end..].starts_with(' '), cut.rfind(' ')) {
+        (false, Some(space)) => &cut[..space],
+        _ => cut,
+    };
+    format!("{}…", cut.trim_end())
+}
+
+/// The `content` of the first `<meta>` tag in `html` whose `property` or `name`
+/// is `key` (ignoring case), e.g. `"og:title"`, with entities decoded.
+pub fn meta_content(html: &str, key: &str) -> Option<String> {
+    // ASCII lowercasing keeps byte offsets, so matches index into `html` too.
+    let lower = html.to_ascii_lowercase();
+    let mut offset = 0;
+
+    while let Some(start) = lower[offset..].find("<meta") {
+        let attributes_start = offset + start + "<meta".len();
+        let (attributes, end) = parse_attributes(&html[attributes_start..]);
+        offset = attributes_start + end;
+
+        let named = attributes.iter().any(|(name, value)| {
+            (name == "property" || name == "name") && value.eq_ignore_ascii_case(key)
+        });
+        let content = attributes
+            .iter()
+            .find(|(name, _)| name == "content")
+            .map(|(_, content)| decode_html_entities(content.trim()))
+            .filter(|content| !content.is_empty());
+        if let (true, Some(content)) = (named, content) {
+            return Some(content);
+        }
+    }
+
+    None
+}
+
+/// The text of the `<title>` in `html`, with whitespace collapsed.
+pub fn html_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+
+    let title = decode_html_entities(&html[start..end]);
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+/// Reads a tag's attributes up to its closing `>`, returning them with
+/// lowercased names alongside the offset just past the `>`.
+fn parse_attributes(tag: &str) -> (Vec<(String, String)>, usize) {
+    let bytes = tag.as_bytes();
+    let skip_whitespace = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        i
+    };
+    let mut attributes = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'>' => return (attributes, i + 1),
+            b'/' => i += 1,
+            byte if byte.is_ascii_whitespace() => i += 1,
+            _ => {
+                let name_start = i;
+                while i < bytes.len()
+                    && !bytes[i].is_ascii_whitespace()
+                    && !matches!(bytes[i], b'=' | b'>' | b'/')
+                {
+                    i += 1;
+                }
+                let name = tag[name_start..i].to_ascii_lowercase();
+
+                let mut value = "";
+                i = skip_whitespace(i);
+                if bytes.get(i) == Some(&b'=') {
+                    i = skip_whitespace(i + 1);
+                    match bytes.get(i) {
+                        Some(&quote @ (b'"' | b'\'')) => {
+                            let value_start = i + 1;
+                            let value_end = tag[value_start..]
+                                .find(quote as char)
+                                .map_or(tag.len(), |end| value_start + end);
+                            value = &tag[value_start..value_end];
+                            i = (value_end + 1).min(tag.len());
+                        }
+                        _ => {
+                            let value_start = i;
+                            while i < bytes.len()
+                                && !bytes[i].is_ascii_whitespace()
+                                && bytes[i] != b'>'
+                            {
+                                i += 1;
+                            }
+                            value = &tag[value_start..i];
+                        }
+                    }
+                }
+                attributes.push((name, value.to_string()));
+            }
+        }
+    }
+
+    (attributes, bytes.len())
+}
+
+/// Converts HN's text HTML to plain text: `<p>` starts a new paragraph, other
+/// tags are dropped and entities are decoded.
+pub fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>').map(|end| start + end) else {
+            break;
+        };
+        text.push_str(&rest[..start]);
+
+        let tag = rest[start + 1..end].trim();
+        let name = tag.split_whitespace().next().unwrap_or_default();
+        if name.eq_ignore_ascii_case("p") || name.trim_end_matches('/').eq_ignore_ascii_case("br") {
+            text.push_str("\n\n");
+        }
+        rest = &rest[end + 1..];
+    }
+
+    text.push_str(rest);
+    decode_html_entities(text.trim())
+}
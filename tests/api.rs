@@ -0,0 +1,435 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+use chrono::Utc;
+use dioxus::prelude::{Readable, ScopeId, Signal, VirtualDom};
+use futures::future::{self, FutureExt, LocalBoxFuture};
+use serde_json::json;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use hackernews::api::{
+    self, ApiClient, FirebaseProvider, RateLimiter, RetryPolicy, StoryBatch, StoryProvider,
+    DEFAULT_USER_AGENT,
+};
+use hackernews::error::ApiError;
+use hackernews::types::{
+    Comment, Feed, Item, LinkPreview, PreviewState, StoryCache, StoryItem, StoryPageData, User,
+};
+
+// ----------------------------------------------------------------
+
+/// A client against `server` that fails fast instead of retrying.
+fn client(server: &MockServer) -> ApiClient {
+    // A fresh `reqwest::Client` per test: each `#[tokio::test]` has its own runtime.
+    ApiClient::new(server.uri())
+        .with_client(reqwest::Client::new())
+        .with_retry_policy(RetryPolicy {
+            max_retries: 0,
+            base_delay: Duration::ZERO,
+        })
+}
+
+fn story(id: i64, title: &str) -> serde_json::Value {
+    json!({
+        "by": "pg",
+        "descendants": 2,
+        "id": id,
+        "kids": [id * 10],
+        "score": 42,
+        "time": 1160418111,
+        "title": title,
+        "type": "story",
+        "url": format!("https://example.com/{id}")
+    })
+}
+
+async fn stub(server: &MockServer, route: &str, body: serde_json::Value) {
+    Mock::given(method("GET"))
+        .and(path(route))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(server)
+        .await;
+}
+
+/// A [`StoryProvider`] serving `stories` from memory; every other id is not found.
+#[derive(Default)]
+struct StubProvider {
+    stories: HashMap<i64, StoryPageData>,
+}
+
+impl StubProvider {
+    fn with(mut self, value: serde_json::Value) -> Self {
+        let story: StoryPageData = serde_json::from_value(value).unwrap();
+        self.stories.insert(story.item.id, story);
+        self
+    }
+
+    fn page(&self, id: i64) -> Result<StoryPageData, ApiError> {
+        self.stories.get(&id).cloned().ok_or(ApiError::NotFound(id))
+    }
+}
+
+impl StoryProvider for StubProvider {
+    fn get_stories(&self, _count: usize) -> LocalBoxFuture<'_, Result<Vec<StoryItem>, ApiError>> {
+        future::ready(Ok(Vec::new())).boxed_local()
+    }
+
+    fn get_story_ids(&self, _feed: Feed) -> LocalBoxFuture<'_, Result<Vec<i64>, ApiError>> {
+        future::ready(Ok(self.stories.keys().copied().collect())).boxed_local()
+    }
+
+    fn resolve(
+        &self,
+        id: i64,
+        _max_depth: usize,
+    ) -> LocalBoxFuture<'_, Result<StoryPageData, ApiError>> {
+        future::ready(self.page(id)).boxed_local()
+    }
+
+    fn get_comments<'a>(
+        &'a self,
+        _ids: &'a [i64],
+        _depth: usize,
+    ) -> LocalBoxFuture<'a, Vec<Comment>> {
+        future::ready(Vec::new()).boxed_local()
+    }
+
+    fn export_story_json(&self, id: i64) -> LocalBoxFuture<'_, Result<String, ApiError>> {
+        future::ready(Err(ApiError::NotFound(id))).boxed_local()
+    }
+
+    fn get_item(&self, id: i64) -> LocalBoxFuture<'_, Result<Item, ApiError>> {
+        future::ready(self.page(id).map(|page| Item::Story(page.item))).boxed_local()
+    }
+
+    fn get_story_preview(&self, id: i64) -> LocalBoxFuture<'_, Result<StoryItem, ApiError>> {
+        future::ready(self.page(id).map(|page| page.item)).boxed_local()
+    }
+
+    fn get_user<'a>(&'a self, username: &'a str) -> LocalBoxFuture<'a, Result<User, ApiError>> {
+        future::ready(Err(ApiError::UserNotFound(username.to_string()))).boxed_local()
+    }
+
+    fn fetch_link_preview<'a>(
+        &'a self,
+        _url: &'a str,
+    ) -> LocalBoxFuture<'a, Result<LinkPreview, ApiError>> {
+        future::ready(Ok(LinkPreview::default())).boxed_local()
+    }
+}
+
+// ----------------------------------------------------------------
+
+#[tokio::test]
+async fn get_stories_parses_the_top_stories() {
+    let server = MockServer::start().await;
+    stub(&server, "/topstories.json", json!([1, 2, 3])).await;
+    stub(&server, "/item/1.json", story(1, "First")).await;
+    stub(&server, "/item/2.json", story(2, "Second")).await;
+    stub(&server, "/item/3.json", story(3, "Third")).await;
+
+    let stories = client(&server).get_stories(2).await.unwrap();
+
+    let titles: Vec<_> = stories.iter().map(|story| story.title.as_str()).collect();
+    assert_eq!(titles, ["First", "Second"]);
+    assert_eq!(stories[0].id, 1);
+    assert_eq!(stories[0].score, 42);
+    assert_eq!(stories[0].by, "pg");
+    assert_eq!(stories[0].url.as_deref(), Some("https://example.com/1"));
+}
+
+#[tokio::test]
+async fn get_stories_skips_items_that_fail_to_load() {
+    let server = MockServer::start().await;
+    stub(&server, "/topstories.json", json!([1, 2, 3])).await;
+    stub(&server, "/item/1.json", story(1, "First")).await;
+    stub(&server, "/item/2.json", json!({ "unexpected": true })).await;
+    stub(&server, "/item/3.json", story(3, "Third")).await;
+
+    let batch = client(&server).get_stories_batch(&[1, 2, 3], 0, 3).await;
+
+    let ids: Vec<_> = batch.stories.iter().map(|story| story.id).collect();
+    assert_eq!(ids, [1, 3]);
+    assert!(matches!(batch.failures[..], [ApiError::Deserialize(_)]));
+}
+
+#[tokio::test]
+async fn missing_items_map_to_not_found() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/item/404.json"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    stub(&server, "/item/405.json", serde_json::Value::Null).await;
+
+    let client = client(&server);
+    assert!(matches!(
+        client.get_story_preview(404).await,
+        Err(ApiError::NotFound(404))
+    ));
+    // HN answers `null` for ids that don't exist.
+    assert!(matches!(
+        client.get_story_preview(405).await,
+        Err(ApiError::NotFound(405))
+    ));
+}
+
+#[tokio::test]
+async fn server_errors_map_to_network_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/topstories.json"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let err = client(&server).get_stories(10).await.unwrap_err();
+    assert!(matches!(err, ApiError::Network(_)));
+    assert!(err.is_transient());
+}
+
+#[tokio::test]
+async fn rate_limited_requests_are_retried_after_the_server_says() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/topstories.json"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        // Once for the client that gives up, once for the one that retries.
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    stub(&server, "/topstories.json", json!([])).await;
+
+    let err = client(&server).get_story_ids(Feed::Top).await.unwrap_err();
+    assert!(matches!(err, ApiError::RateLimited(Some(Duration::ZERO))));
+    assert!(err.is_transient());
+
+    let retrying = client(&server).with_retry_policy(RetryPolicy {
+        max_retries: 1,
+        base_delay: Duration::ZERO,
+    });
+    assert!(retrying.get_story_ids(Feed::Top).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn firebase_provider_works_as_a_trait_object() {
+    let server = MockServer::start().await;
+    stub(&server, "/newstories.json", json!([7])).await;
+    stub(&server, "/item/7.json", story(7, "Seventh")).await;
+
+    let provider: Rc<dyn StoryProvider> = Rc::new(FirebaseProvider::new(client(&server)));
+
+    let ids = provider.get_story_ids(Feed::New).await.unwrap();
+    assert_eq!(ids, [7]);
+    let story = provider.resolve(7, 0).await.unwrap();
+    assert_eq!(story.item.title, "Seventh");
+    assert!(story.comments.is_empty());
+}
+
+#[tokio::test]
+async fn get_stories_returns_what_there_is_when_the_feed_is_short() {
+    let server = MockServer::start().await;
+    stub(&server, "/topstories.json", json!([1, 2, 3])).await;
+    stub(&server, "/item/1.json", story(1, "First")).await;
+    let mut dead = story(2, "Dead");
+    dead["dead"] = json!(true);
+    stub(&server, "/item/2.json", dead).await;
+    stub(&server, "/item/3.json", story(3, "Third")).await;
+
+    let stories = client(&server).get_stories(10).await.unwrap();
+
+    let ids: Vec<_> = stories.iter().map(|story| story.id).collect();
+    assert_eq!(ids, [1, 3]);
+
+    // Pages past the end of the id list are empty rather than out of bounds.
+    let batch = client(&server).get_stories_batch(&[1, 2, 3], 5, 10).await;
+    assert!(batch.stories.is_empty());
+    assert!(batch.failures.is_empty());
+}
+
+#[tokio::test]
+async fn get_stories_batch_reads_on_past_dead_stories() {
+    let server = MockServer::start().await;
+    stub(&server, "/item/1.json", story(1, "First")).await;
+    let mut dead = story(2, "Dead");
+    dead["dead"] = json!(true);
+    stub(&server, "/item/2.json", dead).await;
+    stub(&server, "/item/3.json", story(3, "Third")).await;
+    stub(&server, "/item/4.json", story(4, "Fourth")).await;
+
+    let batch = client(&server).get_stories_batch(&[1, 2, 3, 4], 0, 2).await;
+
+    let ids: Vec<_> = batch.stories.iter().map(|story| story.id).collect();
+    assert_eq!(ids, [1, 3]);
+    // The next page picks up after the story that made up for the dead one.
+    assert_eq!(batch.next_offset, 3);
+}
+
+#[test]
+fn stub_providers_page_through_stories_without_http() {
+    let mut dead = story(2, "Dead");
+    dead["dead"] = json!(true);
+    let provider = StubProvider::default()
+        .with(story(1, "First"))
+        .with(dead)
+        .with(story(3, "Third"));
+
+    let batch: StoryBatch =
+        futures::executor::block_on(provider.get_stories_batch(&[1, 2, 3, 4], 0, 3));
+
+    let ids: Vec<_> = batch.stories.iter().map(|story| story.id).collect();
+    assert_eq!(ids, [1, 3]);
+    assert!(matches!(batch.failures[..], [ApiError::NotFound(4)]));
+    assert_eq!(batch.next_offset, 4);
+}
+
+#[test]
+fn resolve_story_with_previews_from_a_stub_provider() {
+    let provider = StubProvider::default().with(story(1, "First"));
+    // Signals need a runtime to live in, though nothing is rendered.
+    let dom = VirtualDom::new(|| None);
+
+    dom.in_runtime(|| {
+        ScopeId::ROOT.in_runtime(|| {
+            let cache = Signal::new(StoryCache::default());
+            let preview_state = Signal::new(PreviewState::Unset);
+
+            futures::executor::block_on(api::resolve_story_with(
+                &provider,
+                cache,
+                preview_state,
+                1,
+                0,
+            ));
+            assert_eq!(preview_state.read().story_id(), Some(1));
+            assert!(cache.read().get(1).is_some());
+
+            futures::executor::block_on(api::resolve_story_with(
+                &provider,
+                cache,
+                preview_state,
+                2,
+                0,
+            ));
+            assert!(matches!(
+                *preview_state.read(),
+                PreviewState::Failed {
+                    story_id: 2,
+                    timed_out: false
+                }
+            ));
+        })
+    });
+}
+
+#[tokio::test]
+async fn requests_carry_the_user_agent() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/topstories.json"))
+        .and(header("user-agent", DEFAULT_USER_AGENT))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/newstories.json"))
+        .and(header("user-agent", "my-mirror/1.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .mount(&server)
+        .await;
+
+    assert!(client(&server).get_stories(10).await.unwrap().is_empty());
+    let custom = client(&server).with_user_agent("my-mirror/1.0");
+    assert!(custom.get_new_stories(10).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn rate_limiter_spaces_out_requests_after_a_burst() {
+    let limiter = RateLimiter::new(10.0);
+    let started = Utc::now();
+
+    // The first second's worth goes out at once...
+    for _ in 0..10 {
+        limiter.acquire().await;
+    }
+    assert!(Utc::now() - started < chrono::Duration::milliseconds(50));
+
+    // ...after which each request waits for its token.
+    limiter.acquire().await;
+    limiter.acquire().await;
+    assert!(Utc::now() - started >= chrono::Duration::milliseconds(150));
+}
+
+#[tokio::test]
+async fn link_previews_skip_pages_that_arent_html() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/post"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            r#"<meta property="og:description" content="A post"><meta property="og:image" content="card.png">"#,
+            "text/html; charset=utf-8",
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/paper.pdf"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw("%PDF-1.7", "application/pdf"))
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let preview = client
+        .fetch_link_preview(&format!("{}/post", server.uri()))
+        .await
+        .unwrap();
+    assert_eq!(preview.description.as_deref(), Some("A post"));
+    assert_eq!(preview.image, Some(format!("{}/card.png", server.uri())));
+
+    let pdf = client
+        .fetch_link_preview(&format!("{}/paper.pdf", server.uri()))
+        .await
+        .unwrap();
+    assert!(pdf.is_empty());
+}
+
+#[tokio::test]
+async fn link_previews_stop_reading_huge_pages() {
+    let server = MockServer::start().await;
+    // The tags sit past the limit, so they are never reached.
+    let page = format!(
+        "<html><head>{}<meta property=\"og:description\" content=\"Too far\"></head></html>",
+        " ".repeat(1024 * 1024)
+    );
+    Mock::given(method("GET"))
+        .and(path("/huge"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(page, "text/html"))
+        .mount(&server)
+        .await;
+
+    let preview = client(&server)
+        .fetch_link_preview(&format!("{}/huge", server.uri()))
+        .await
+        .unwrap();
+    assert!(preview.is_empty());
+}
@@ -0,0 +1,183 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+use chrono::{TimeZone, Utc};
+
+use hackernews::render::{sparkline_points, thread_to_text_at, to_markdown_at, to_rss, to_text};
+use hackernews::types::{StoryItem, StoryPageData};
+
+// ----------------------------------------------------------------
+
+fn story(json: &str) -> StoryItem {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn to_rss_wraps_items_in_a_channel() {
+    let rss = to_rss(&[]);
+
+    assert!(rss.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">"));
+    assert!(rss.contains("<channel>\n<title>Hacker News</title>"));
+    assert!(rss.trim_end().ends_with("</channel>\n</rss>"));
+    assert!(!rss.contains("<item>"));
+}
+
+#[test]
+fn to_rss_describes_each_story() {
+    let rss = to_rss(&[story(
+        r#"{"id": 8863, "by": "dhouston", "score": 104, "descendants": 71, "time": 1175714200,
+            "title": "My YC app: Dropbox", "type": "story", "url": "http://www.getdropbox.com/"}"#,
+    )]);
+
+    assert_eq!(rss.matches("<item>").count(), 1);
+    assert!(rss.contains("<title>My YC app: Dropbox</title>"));
+    assert!(rss.contains("<link>http://www.getdropbox.com/</link>"));
+    assert!(rss.contains("<comments>https://news.ycombinator.com/item?id=8863</comments>"));
+    assert!(rss.contains("<pubDate>Wed, 4 Apr 2007 19:16:40 +0000</pubDate>"));
+    assert!(rss.contains("<description>104 points by dhouston · 71 comments</description>"));
+}
+
+#[test]
+fn to_rss_links_self_posts_to_the_discussion_and_escapes_text() {
+    let rss = to_rss(&[story(
+        r#"{"id": 1, "title": "Ask HN: <b> & \"tags\"?", "type": "story"}"#,
+    )]);
+
+    assert!(rss.contains("<title>Ask HN: &lt;b&gt; &amp; &quot;tags&quot;?</title>"));
+    assert!(rss.contains("<link>https://news.ycombinator.com/item?id=1</link>"));
+    // Without a time there is no date to publish.
+    assert!(!rss.contains("<pubDate>"));
+}
+
+#[test]
+fn sparkline_points_span_the_box() {
+    assert_eq!(sparkline_points(&[5], 40.0, 10.0), None);
+    assert_eq!(
+        sparkline_points(&[1, 3, 2], 40.0, 10.0).as_deref(),
+        Some("0.0,10.0 20.0,0.0 40.0,5.0")
+    );
+    // Unchanged scores draw a flat line through the middle.
+    assert_eq!(
+        sparkline_points(&[7, 7], 40.0, 10.0).as_deref(),
+        Some("0.0,5.0 40.0,5.0")
+    );
+}
+
+#[test]
+fn to_text_lists_rank_title_host_and_counts() {
+    let text = to_text(&[
+        story(
+            r#"{"id": 8863, "by": "dhouston", "score": 1543, "descendants": 71, "time": 1175714200,
+                "title": "My YC app: Dropbox", "type": "story", "url": "http://www.getdropbox.com/"}"#,
+        ),
+        story(
+            r#"{"id": 1, "by": "pg", "score": 1, "descendants": 1, "title": "Ask HN: Hi", "type": "story"}"#,
+        ),
+    ]);
+
+    assert_eq!(
+        text,
+        concat!(
+            "  1. My YC app: Dropbox (getdropbox.com)\n",
+            "     1,543 points by dhouston | 71 comments\n",
+            "  2. Ask HN: Hi\n",
+            "     1 point by pg | 1 comment\n",
+        )
+    );
+}
+
+#[test]
+fn thread_to_text_indents_replies_by_depth() {
+    let page: StoryPageData = serde_json::from_str(
+        r#"{"id": 1, "by": "pg", "score": 42, "descendants": 3, "time": 1160418111,
+            "title": "Ask HN: Tabs &amp; spaces?", "text": "<p>Which one?", "type": "story",
+            "comments": [
+                {"id": 2, "by": "alice", "time": 1160418111, "type": "comment",
+                 "text": "Tabs.<p>Always.", "sub_comments": [
+                    {"id": 3, "by": "bob", "time": 1160418111, "type": "comment",
+                     "text": "Spaces &gt; tabs"}
+                 ]},
+                {"id": 4, "deleted": true, "time": 1160418111, "type": "comment"}
+            ]}"#,
+    )
+    .unwrap();
+    let now = Utc.timestamp_opt(1160418111 + 2 * 60 * 60, 0).unwrap();
+
+    assert_eq!(
+        thread_to_text_at(&page, now),
+        concat!(
+            "Ask HN: Tabs & spaces?\n",
+            "42 points by pg | 2 hours ago | 3 comments\n",
+            "\n",
+            "Which one?\n",
+            "\n",
+            "alice · 2 hours ago\n",
+            "Tabs.\n",
+            "\n",
+            "Always.\n",
+            "\n",
+            "  bob · 2 hours ago\n",
+            "  Spaces > tabs\n",
+            "\n",
+            "[deleted]\n",
+        )
+    );
+}
+
+#[test]
+fn to_markdown_nests_replies_in_blockquotes() {
+    let page: StoryPageData = serde_json::from_str(
+        r#"{"id": 1, "by": "pg", "score": 1, "descendants": 3, "time": 1160418111,
+            "title": "Tabs", "url": "https://example.com/tabs", "type": "story",
+            "comments": [
+                {"id": 2, "by": "alice", "time": 1160418111, "type": "comment",
+                 "text": "<i>Tabs</i>.<p>Always.", "sub_comments": [
+                    {"id": 3, "by": "bob", "time": 1160418111, "type": "comment",
+                     "text": "Spaces"}
+                 ]},
+                {"id": 4, "deleted": true, "time": 1160418111, "type": "comment"}
+            ]}"#,
+    )
+    .unwrap();
+    let now = Utc.timestamp_opt(1160418111 + 2 * 60 * 60, 0).unwrap();
+
+    assert_eq!(
+        to_markdown_at(&page, now),
+        concat!(
+            "# [Tabs](https://example.com/tabs)\n",
+            "\n",
+            "1 point by pg · 2 hours ago\n",
+            "\n",
+            "> **alice** · 2 hours ago\n",
+            ">\n",
+            "> Tabs.\n",
+            ">\n",
+            "> Always.\n",
+            ">\n",
+            "> > **bob** · 2 hours ago\n",
+            "> >\n",
+            "> > Spaces\n",
+            "> >\n",
+            // Closing each quote keeps siblings from merging into one.
+            ">\n",
+            "\n",
+            "> [deleted]\n",
+            ">\n",
+        )
+    );
+}
@@ -0,0 +1,403 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+use chrono::{TimeZone, Utc};
+
+use hackernews::types::{
+    Comment, Item, ItemType, LinkPreview, ScoreHistory, Settings, StoryItem, StoryPageData,
+};
+
+// ----------------------------------------------------------------
+
+// Payloads captured from https://hacker-news.firebaseio.com/v0/item/<id>.json
+
+const STORY: &str = r#"{
+    "by": "dhouston",
+    "descendants": 71,
+    "id": 8863,
+    "kids": [9224, 8917, 8884],
+    "score": 104,
+    "time": 1175714200,
+    "title": "My YC app: Dropbox - Throw away your USB drive",
+    "type": "story",
+    "url": "http://www.getdropbox.com/u/2/screencast.html"
+}"#;
+
+const JOB: &str = r#"{
+    "by": "justin",
+    "id": 192327,
+    "score": 6,
+    "time": 1210981217,
+    "title": "Justin.tv is looking for a Lead Flash Engineer!",
+    "type": "job"
+}"#;
+
+const COMMENT: &str = r#"{
+    "by": "norvig",
+    "id": 2921983,
+    "kids": [2922097, 2922429, 2924562],
+    "parent": 2921506,
+    "text": "Aw shucks, guys ... you make me blush with your compliments.",
+    "time": 1314211127,
+    "type": "comment"
+}"#;
+
+// ----------------------------------------------------------------
+
+#[test]
+fn deserializes_story() {
+    let story: StoryItem = serde_json::from_str(STORY).unwrap();
+
+    assert_eq!(story.id, 8863);
+    assert_eq!(story.by, "dhouston");
+    assert_eq!(
+        story.title,
+        "My YC app: Dropbox - Throw away your USB drive"
+    );
+    assert_eq!(
+        story.url.as_deref(),
+        Some("http://www.getdropbox.com/u/2/screencast.html")
+    );
+    assert_eq!(story.text, None);
+    assert_eq!(story.score, 104);
+    assert_eq!(story.descendants, 71);
+    assert_eq!(story.kids, vec![9224, 8917, 8884]);
+    assert_eq!(story.time, Some(Utc.timestamp_opt(1175714200, 0).unwrap()));
+    assert_eq!(story.item_type(), ItemType::Story);
+    assert!(story.parts.is_empty());
+    assert!(!story.deleted);
+    assert!(!story.dead);
+}
+
+#[test]
+fn deserializes_job_without_url_or_text() {
+    let job: StoryItem = serde_json::from_str(JOB).unwrap();
+
+    assert_eq!(job.id, 192327);
+    assert_eq!(job.by, "justin");
+    assert_eq!(job.url, None);
+    assert_eq!(job.text, None);
+    assert_eq!(job.score, 6);
+    assert_eq!(job.time, Some(Utc.timestamp_opt(1210981217, 0).unwrap()));
+    assert_eq!(job.item_type(), ItemType::Job);
+    // Jobs have no discussion, so these fall back to their defaults.
+    assert_eq!(job.descendants, 0);
+    assert!(job.kids.is_empty());
+}
+
+#[test]
+fn deserializes_comment_with_kids() {
+    let comment: Comment = serde_json::from_str(COMMENT).unwrap();
+
+    assert_eq!(comment.id, 2921983);
+    assert_eq!(comment.by, "norvig");
+    assert_eq!(
+        comment.text,
+        "Aw shucks, guys ... you make me blush with your compliments."
+    );
+    assert_eq!(comment.kids, vec![2922097, 2922429, 2924562]);
+    assert_eq!(comment.parent, Some(2921506));
+    assert_eq!(comment.time, Utc.timestamp_opt(1314211127, 0).unwrap());
+    assert_eq!(comment.item_type(), ItemType::Comment);
+    // Replies are resolved separately and never part of the payload.
+    assert!(comment.sub_comments.is_empty());
+    assert_eq!(comment.placeholder(), None);
+}
+
+#[test]
+fn missing_optional_fields_use_defaults() {
+    let story: StoryItem =
+        serde_json::from_str(r#"{"id": 1, "title": "Hi", "time": 0, "type": "story"}"#).unwrap();
+    assert_eq!(story.by, "");
+    assert_eq!(story.score, 0);
+    assert_eq!(story.descendants, 0);
+    assert!(story.kids.is_empty());
+    assert_eq!(story.url, None);
+    assert!(story.time.is_some());
+
+    // Deleted comments keep only their id, time and type.
+    let comment: Comment =
+        serde_json::from_str(r#"{"id": 2, "deleted": true, "time": 0, "type": "comment"}"#)
+            .unwrap();
+    assert_eq!(comment.by, "");
+    assert_eq!(comment.text, "");
+    assert!(comment.kids.is_empty());
+    assert_eq!(comment.placeholder(), Some("[deleted]"));
+}
+
+#[test]
+fn tolerates_missing_or_null_time() {
+    let missing: StoryItem =
+        serde_json::from_str(r#"{"id": 4, "title": "No time", "type": "story"}"#).unwrap();
+    assert_eq!(missing.time, None);
+
+    let null: StoryItem =
+        serde_json::from_str(r#"{"id": 5, "title": "Null time", "time": null, "type": "story"}"#)
+            .unwrap();
+    assert_eq!(null.time, None);
+}
+
+#[test]
+fn decodes_entities_in_titles() {
+    let story: StoryItem = serde_json::from_str(
+        r#"{"id": 3, "title": "Ask HN: Q&amp;A &#x2F; &quot;tips&quot;", "time": 0, "type": "story"}"#,
+    )
+    .unwrap();
+    assert_eq!(story.display_title(), "Ask HN: Q&A / \"tips\"");
+}
+
+#[test]
+fn titles_survive_a_save_and_restore() {
+    let story: StoryItem = serde_json::from_str(
+        r#"{"id": 4, "title": "Escape &amp;amp; in HTML", "time": 0, "type": "story"}"#,
+    )
+    .unwrap();
+    let restored: StoryItem =
+        serde_json::from_str(&serde_json::to_string(&story).unwrap()).unwrap();
+    assert_eq!(restored, story);
+    assert_eq!(restored.display_title(), "Escape &amp; in HTML");
+}
+
+#[test]
+fn deserializes_items_by_type() {
+    let story: Item = serde_json::from_str(STORY).unwrap();
+    assert!(matches!(story, Item::Story(ref story) if story.id == 8863));
+
+    let job: Item = serde_json::from_str(JOB).unwrap();
+    assert!(matches!(job, Item::Job(_)));
+    assert_eq!(job.id(), 192327);
+
+    let comment: Item = serde_json::from_str(COMMENT).unwrap();
+    assert!(matches!(comment, Item::Comment(ref comment) if comment.by == "norvig"));
+}
+
+#[test]
+fn rejects_items_of_unknown_type() {
+    let result = serde_json::from_str::<Item>(r#"{"id": 6, "time": 0, "type": "ad"}"#);
+    assert!(result.is_err());
+}
+
+#[test]
+fn descendant_count_includes_nested_replies() {
+    let mut comment: Comment = serde_json::from_str(COMMENT).unwrap();
+    assert_eq!(comment.descendant_count(), 0);
+
+    let mut reply = comment.clone();
+    reply.sub_comments = vec![comment.clone(), comment.clone()];
+    comment.sub_comments = vec![reply, comment.clone()];
+    assert_eq!(comment.descendant_count(), 4);
+}
+
+#[test]
+fn muted_domains_cover_subdomains() {
+    let mut settings = Settings::default();
+    settings.muted_domains.insert("example.com".to_string());
+
+    assert!(settings.is_domain_muted("example.com"));
+    assert!(settings.is_domain_muted("blog.example.com"));
+    assert!(!settings.is_domain_muted("notexample.com"));
+    assert!(!settings.is_domain_muted("example.org"));
+}
+
+#[test]
+fn muted_keywords_match_titles_ignoring_case() {
+    let mut settings = Settings::default();
+    settings.mute_keyword("  Crypto ");
+    settings.mute_keyword("crypto");
+    settings.mute_keyword("   ");
+    assert_eq!(settings.muted_keywords, ["Crypto"]);
+
+    assert!(settings.is_title_muted("The CRYPTO winter is over"));
+    assert!(!settings.is_title_muted("Show HN: A Rust web framework"));
+}
+
+#[test]
+fn tolerates_missing_title() {
+    let story: StoryItem = serde_json::from_str(r#"{"id": 7, "time": 0, "type": "poll"}"#).unwrap();
+    assert_eq!(story.title, "");
+    assert_eq!(story.display_title(), "(untitled)");
+
+    let null: StoryItem =
+        serde_json::from_str(r#"{"id": 8, "title": null, "time": 0, "type": "story"}"#).unwrap();
+    assert_eq!(null.display_title(), "(untitled)");
+}
+
+#[test]
+fn thread_matches_keeps_ancestors_of_matching_replies() {
+    let mut comment: Comment = serde_json::from_str(COMMENT).unwrap();
+    let mut reply = comment.clone();
+    reply.text = "<p>Try <i>Rust</i> instead</p>".to_string();
+    comment.sub_comments = vec![reply];
+
+    assert!(!comment.text_matches("rust"));
+    assert!(comment.thread_matches("rust"));
+    assert_eq!(comment.match_count("rust"), 1);
+    assert!(!comment.thread_matches("python"));
+    // Clearing the query shows the whole thread again.
+    assert_eq!(comment.match_count(""), 2);
+}
+
+#[test]
+fn replies_loaded_on_demand_are_searched() {
+    // Fetched one level deep, so the matching reply arrives with "show N replies".
+    let story: StoryItem = serde_json::from_str(STORY).unwrap();
+    let comment: Comment = serde_json::from_str(COMMENT).unwrap();
+    let mut page = StoryPageData {
+        comments: vec![comment.clone()],
+        item: story,
+        poll_options: Vec::new(),
+    };
+    assert!(!page.comments[0].thread_matches("rust"));
+
+    let mut reply = comment.clone();
+    reply.id = 2922097;
+    reply.text = "<p>Try <i>Rust</i> instead</p>".to_string();
+    page.comment_mut(comment.id).unwrap().sub_comments = vec![reply];
+    assert!(page.comments[0].thread_matches("rust"));
+    assert_eq!(page.comments[0].match_count("rust"), 1);
+    assert!(page.comment_mut(2922097).is_some());
+    assert!(page.comment_mut(1).is_none());
+}
+
+#[test]
+fn read_later_keeps_insertion_order() {
+    let mut settings = Settings::default();
+    settings.toggle_read_later(3);
+    settings.toggle_read_later(1);
+    settings.toggle_read_later(2);
+    settings.toggle_read_later(1);
+
+    assert_eq!(settings.read_later, [3, 2]);
+    assert!(settings.is_read_later(2));
+    assert!(!settings.is_read_later(1));
+}
+
+#[test]
+fn favorites_keep_the_order_they_were_starred() {
+    let mut settings = Settings::default();
+    settings.toggle_favorite(30);
+    settings.toggle_favorite(10);
+    settings.toggle_favorite(20);
+    settings.toggle_favorite(10);
+
+    assert_eq!(settings.favorites, [30, 20]);
+    assert!(settings.is_favorite(20));
+    assert!(!settings.is_favorite(10));
+}
+
+#[test]
+fn share_text_links_the_discussion() {
+    let story: StoryItem = serde_json::from_str(STORY).unwrap();
+    assert_eq!(
+        story.share_text(),
+        "My YC app: Dropbox - Throw away your USB drive — https://news.ycombinator.com/item?id=8863"
+    );
+}
+
+#[test]
+fn hidden_comment_count_compares_a_fully_fetched_tree() {
+    let story: StoryItem = serde_json::from_str(STORY).unwrap();
+    let mut leaf: Comment = serde_json::from_str(COMMENT).unwrap();
+    leaf.kids.clear();
+    let page = StoryPageData {
+        item: StoryItem {
+            descendants: 5,
+            ..story
+        },
+        comments: vec![leaf.clone(), leaf.clone()],
+        poll_options: Vec::new(),
+    };
+    assert_eq!(page.fetched_comment_count(), 2);
+    assert_eq!(page.hidden_comment_count(), 3);
+}
+
+#[test]
+fn hidden_comment_count_compares_only_fetched_levels() {
+    // Fetched one level deep: the first comment's replies load on demand.
+    let story: StoryItem = serde_json::from_str(STORY).unwrap();
+    let comment: Comment = serde_json::from_str(COMMENT).unwrap();
+    let mut page = StoryPageData {
+        comments: vec![comment.clone(), comment.clone()],
+        item: story,
+        poll_options: Vec::new(),
+    };
+    // One of the story's three top-level kids didn't come back.
+    assert_eq!(page.hidden_comment_count(), 1);
+
+    // Replies that were fetched count against their parent's `kids`.
+    let mut reply = comment.clone();
+    reply.kids.clear();
+    page.comments[1].sub_comments = vec![reply];
+    assert_eq!(page.hidden_comment_count(), 3);
+}
+
+#[test]
+fn score_history_keeps_the_latest_samples() {
+    let mut history = ScoreHistory::default();
+    let start = Utc.timestamp_opt(0, 0).unwrap();
+    for sample in 0..ScoreHistory::MAX_SAMPLES as i64 + 2 {
+        history.record(
+            1,
+            start + chrono::Duration::minutes(sample),
+            sample,
+            sample * 10,
+        );
+    }
+
+    let scores = history.scores(1);
+    assert_eq!(scores.len(), ScoreHistory::MAX_SAMPLES);
+    assert_eq!(scores[0].1, 2);
+    let comments = history.comments(1);
+    assert_eq!(comments.len(), ScoreHistory::MAX_SAMPLES);
+    assert_eq!(comments[0].1, 20);
+    assert!(history.scores(2).is_empty());
+    assert!(history.comments(2).is_empty());
+}
+
+#[test]
+fn link_preview_reads_open_graph_tags() {
+    let preview = LinkPreview::from_html(
+        r#"<html><head>
+            <title>Fallback</title>
+            <meta property="og:title" content="Dropbox &amp; you">
+            <META content='Throw away your USB drive' Property='og:description' />
+            <meta property="og:image" content="/images/card.png">
+        </head></html>"#,
+        "https://www.getdropbox.com/u/2/screencast.html",
+    );
+    assert_eq!(preview.title.as_deref(), Some("Dropbox & you"));
+    assert_eq!(
+        preview.description.as_deref(),
+        Some("Throw away your USB drive")
+    );
+    assert_eq!(
+        preview.image.as_deref(),
+        Some("https://www.getdropbox.com/images/card.png")
+    );
+
+    // Without OpenGraph tags, the title and meta description stand in.
+    let plain = LinkPreview::from_html(
+        "<title>\n  My blog\n</title><meta name=description content=Posts>",
+        "https://example.com/",
+    );
+    assert_eq!(plain.title.as_deref(), Some("My blog"));
+    assert_eq!(plain.description.as_deref(), Some("Posts"));
+    assert_eq!(plain.image, None);
+
+    assert!(LinkPreview::from_html("<p>Nothing here</p>", "https://example.com/").is_empty());
+}
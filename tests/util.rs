@@ -0,0 +1,155 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+use chrono::{TimeZone, Utc};
+
+use hackernews::util::{
+    clean_url, comments_label, excerpt, format_count, host_of, points_label, reading_minutes,
+    sanitize_html, time_tooltip, word_count,
+};
+
+// ----------------------------------------------------------------
+
+#[test]
+fn host_of_drops_port_and_path() {
+    assert_eq!(
+        host_of("https://sub.example.com:8080/path").as_deref(),
+        Some("sub.example.com")
+    );
+}
+
+#[test]
+fn host_of_lowercases_scheme_and_host() {
+    assert_eq!(
+        host_of("HTTP://Example.com").as_deref(),
+        Some("example.com")
+    );
+    assert_eq!(
+        host_of("https://www.Example.com/").as_deref(),
+        Some("example.com")
+    );
+}
+
+#[test]
+fn host_of_accepts_urls_without_a_scheme() {
+    assert_eq!(
+        host_of("example.com/https://x").as_deref(),
+        Some("example.com")
+    );
+    assert_eq!(host_of("www.example.org").as_deref(), Some("example.org"));
+}
+
+#[test]
+fn host_of_rejects_empty_and_hostless_urls() {
+    assert_eq!(host_of(""), None);
+    assert_eq!(host_of("mailto:someone@example.com"), None);
+}
+
+#[test]
+fn word_count_ignores_markup() {
+    let html = "<p>The quick brown fox</p><p>jumps over the <i>lazy</i> dog&#x27;s back.</p>";
+    assert_eq!(word_count(html), 10);
+}
+
+#[test]
+fn reading_minutes_rounds_up_at_200_wpm() {
+    assert_eq!(reading_minutes("short"), 1);
+    assert_eq!(reading_minutes(&"word ".repeat(200)), 1);
+    assert_eq!(reading_minutes(&"word ".repeat(201)), 2);
+    assert_eq!(reading_minutes(&"word ".repeat(800)), 4);
+}
+
+#[test]
+fn excerpt_keeps_short_text_whole() {
+    assert_eq!(
+        excerpt("<p>Ask HN: is this <i>fine</i>?</p>", 200),
+        "Ask HN: is this fine?"
+    );
+}
+
+#[test]
+fn excerpt_cuts_at_a_word_boundary() {
+    assert_eq!(excerpt("one two three four", 9), "one two…");
+    assert_eq!(excerpt("one two three four", 7), "one two…");
+    assert_eq!(excerpt("supercalifragilistic", 5), "super…");
+}
+
+#[test]
+fn excerpt_never_splits_entities() {
+    assert_eq!(excerpt("Q&amp;A&#x2F;tips for you", 4), "Q&A/…");
+}
+
+#[test]
+fn format_count_groups_thousands() {
+    assert_eq!(format_count(0), "0");
+    assert_eq!(format_count(999), "999");
+    assert_eq!(format_count(1000), "1,000");
+    assert_eq!(format_count(1234567), "1,234,567");
+    assert_eq!(format_count(-1543), "-1,543");
+}
+
+#[test]
+fn time_tooltip_ends_with_the_utc_time() {
+    let time = Utc.with_ymd_and_hms(2024, 3, 1, 13, 4, 5).unwrap();
+    assert!(time_tooltip(time).ends_with(" (2024-03-01 13:04 UTC)"));
+}
+
+#[test]
+fn clean_url_strips_tracking_params() {
+    assert_eq!(
+        clean_url(
+            "https://example.com/post?id=7&utm_source=hn&UTM_Medium=social&fbclid=abc#comments"
+        ),
+        "https://example.com/post?id=7#comments"
+    );
+    assert_eq!(
+        clean_url("https://example.com/?gclid=1&q=a+b%20c&utm_campaign=x"),
+        "https://example.com/?q=a+b%20c"
+    );
+    assert_eq!(
+        clean_url("https://example.com/page?utm_source=hn"),
+        "https://example.com/page"
+    );
+    // Nothing to strip, or nothing parseable: left exactly as submitted.
+    assert_eq!(
+        clean_url("HTTPS://Example.com/a?b=1&utm=2"),
+        "HTTPS://Example.com/a?b=1&utm=2"
+    );
+    assert_eq!(
+        clean_url("not a url?utm_source=x"),
+        "not a url?utm_source=x"
+    );
+}
+
+#[test]
+fn labels_agree_with_their_counts() {
+    assert_eq!(points_label(1), "1 point");
+    assert_eq!(points_label(42), "42 points");
+    assert_eq!(points_label(0), "0 points");
+    assert_eq!(points_label(1234), "1,234 points");
+    assert_eq!(comments_label(1), "1 comment");
+    assert_eq!(comments_label(71), "71 comments");
+}
+
+#[test]
+fn sanitize_html_drops_scripts_with_their_contents() {
+    assert_eq!(
+        sanitize_html("<p>Hi<script>alert(1)</script><style>p { color: red }</style></p>"),
+        "<p>Hi</p>"
+    );
+}